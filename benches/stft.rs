@@ -0,0 +1,73 @@
+#![feature(test)]
+extern crate audio_featrs;
+#[macro_use]
+extern crate ndarray;
+extern crate rustfft;
+extern crate test;
+
+use audio_featrs::{Stft, StftBuilder};
+use ndarray::prelude::*;
+use ndarray::Zip;
+use rustfft::num_complex::Complex;
+use rustfft::FFTplanner;
+use test::Bencher;
+
+/// STFT using a complex FFT of size `n_fft` for every frame, as done before the real FFT.
+fn stft_complex_fft(stft: &Stft<f32>, signal: &[f32]) -> Array2<f32> {
+    let fft = FFTplanner::new(false).plan_fft(stft.n_fft);
+    let n_frames = 1 + (signal.len() - stft.n_fft) / stft.hop_length;
+    let n_freqs = stft.n_fft / 2 + 1;
+    let mut fft_input = Array1::<Complex<f32>>::zeros(stft.n_fft);
+    let mut fft_output = Array1::<Complex<f32>>::zeros(stft.n_fft);
+    let mut output = Array2::<f32>::zeros((n_freqs, n_frames).f());
+    for frame in 0..n_frames {
+        let start = frame * stft.hop_length;
+        Zip::from(&mut fft_input)
+            .and(&stft.window)
+            .and(&signal[start..start + stft.n_fft])
+            .apply(|i, &w, &a| *i = Complex::new(w * a, 0.));
+        fft.process(
+            fft_input.as_slice_mut().unwrap(),
+            fft_output.as_slice_mut().unwrap(),
+        );
+        output.slice_mut(s![.., frame]).assign(
+            &fft_output
+                .slice(s![0..n_freqs])
+                .map(|v| (v / stft.normalization).norm()),
+        );
+    }
+    output
+}
+
+fn _setup(n_fft: usize) -> (Stft<f32>, Vec<f32>) {
+    let signal = (0..16000 * 10)
+        .map(|i| (i as f32 * 0.01).sin())
+        .collect::<Vec<f32>>();
+    let stft = StftBuilder::new()
+        .n_fft(n_fft)
+        .hop_length(n_fft / 4)
+        .build()
+        .unwrap();
+    (stft, signal)
+}
+
+#[bench]
+fn bench_stft_real_fft_512(b: &mut Bencher) {
+    let (stft, signal) = _setup(512);
+    b.iter(|| stft.process(signal.clone()).unwrap());
+}
+#[bench]
+fn bench_stft_complex_fft_512(b: &mut Bencher) {
+    let (stft, signal) = _setup(512);
+    b.iter(|| stft_complex_fft(&stft, &signal.clone()));
+}
+#[bench]
+fn bench_stft_real_fft_2048(b: &mut Bencher) {
+    let (stft, signal) = _setup(2048);
+    b.iter(|| stft.process(signal.clone()).unwrap());
+}
+#[bench]
+fn bench_stft_complex_fft_2048(b: &mut Bencher) {
+    let (stft, signal) = _setup(2048);
+    b.iter(|| stft_complex_fft(&stft, &signal.clone()));
+}
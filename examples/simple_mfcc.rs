@@ -0,0 +1,35 @@
+extern crate audio_featrs;
+extern crate ndarray;
+extern crate ndarray_rand;
+extern crate rand;
+
+use audio_featrs::{MfccBuilder, PadMode, StftBuilder, Window};
+use ndarray::prelude::*;
+use ndarray_rand::{RandomExt, F32};
+use rand::distributions::Normal;
+
+type Result<T> = ::std::result::Result<T, Box<::std::error::Error>>;
+
+fn main() -> Result<()> {
+    let sr = 16000;
+    let size = 16000;
+    let x = Array1::random(size, F32(Normal::new(-1., 1.)));
+    let stft = StftBuilder::new()
+        .n_fft(512)
+        .hop_length(160)
+        .normalize(false)
+        .pad_mode(PadMode::Reflect)
+        .window_named(Window::Hamming)
+        .build()?;
+    let mfcc = MfccBuilder::new()
+        .stft(stft)
+        .sr(sr)
+        .n_mels(40)
+        .n_mfcc(13)
+        .lifter(22.)
+        .build()?;
+
+    let coefficients = mfcc.process(&x)?;
+    println!("{:?}", coefficients.shape());
+    Ok(())
+}
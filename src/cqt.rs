@@ -0,0 +1,675 @@
+use std::f64::consts::PI;
+
+use ndarray::prelude::*;
+use ndarray::AsArray;
+use num_traits::Zero;
+use rustfft::num_complex::Complex;
+use rustfft::FFTplanner;
+
+use crate::filters;
+use crate::spectrum::{PadMode, Stft, StftBuilder};
+use crate::windows::Window;
+use crate::StftNum;
+
+type Result<T> = ::std::result::Result<T, Box<::std::error::Error>>;
+
+/// Pass-band of the `kaiser_fast` resampling filter relative to Nyquist.
+const BW_FASTEST: f64 = 0.85;
+
+/// Constant- or variable-Q transform with logarithmically spaced frequency bins.
+///
+/// Bin `k` is centered at `f_k = fmin * 2^((k + tuning) / bins_per_octave)`. The filters of
+/// an octave are precomputed as a sparse kernel in the frequency domain and applied to the FFT
+/// frames of the signal. Lower octaves are computed on a signal that is recursively downsampled
+/// by two. Follows `librosa.cqt(..., pad_mode="reflect", res_type="kaiser_fast")`.
+///
+/// With a bandwidth offset `gamma > 0` the bandwidth of bin `k` is `alpha * f_k + gamma` with
+/// `alpha = 2^(1 / bins_per_octave) - 1`, which gives the variable-Q transform of `librosa.vqt`.
+/// The low frequency filters become shorter and have a better time resolution. A `gamma` of
+/// `24.7 * alpha / 0.108` follows the equivalent rectangular bandwidth of the auditory filters.
+///
+/// In the `hybrid` mode, the bins whose filters fit into two hops are computed from the
+/// magnitude of a Hann windowed STFT instead, as done by `librosa.hybrid_cqt`. This mode only
+/// computes magnitudes.
+pub struct Cqt<T> {
+    pub sr: T,
+    pub hop_length: usize,
+    pub fmin: T,
+    pub n_bins: usize,
+    pub bins_per_octave: usize,
+    pub tuning: T,
+    pub filter_scale: T,
+    /// Bandwidth offset in Hz, 0 for the constant-Q transform.
+    pub gamma: T,
+    /// Fraction of the energy of every filter that is discarded to sparsify the kernel.
+    pub sparsity: T,
+    /// Scales the output by `1 / sqrt(length)` of the filters.
+    pub scale: bool,
+    pub hybrid: bool,
+    /// Filter lengths in samples at `sr`.
+    pub lengths: Array1<T>,
+    early_downsample: Option<Downsampler<T>>,
+    octaves: Vec<Octave<T>>,
+    pseudo: Option<PseudoCqt<T>>,
+}
+
+/// Nonzero FFT bins and values of every filter.
+type SparseKernel<T> = Vec<Vec<(usize, Complex<T>)>>;
+
+/// Sparse kernel and framing of one octave.
+struct Octave<T> {
+    // The signal is downsampled by two before this octave
+    downsample: bool,
+    stft: Stft<T>,
+    // Filters from low to high frequencies
+    kernel: SparseKernel<T>,
+}
+
+/// Magnitude response of the highest bins, computed from the STFT magnitude.
+struct PseudoCqt<T> {
+    stft: Stft<T>,
+    kernel: Vec<Vec<(usize, T)>>,
+    // Output scale of every bin
+    norms: Vec<T>,
+}
+
+/// Filter parameters shared by all octaves.
+#[derive(Clone, Copy)]
+struct FilterParams<T> {
+    bins_per_octave: usize,
+    window: Window,
+    filter_scale: T,
+    gamma: T,
+    sparsity: T,
+}
+
+#[derive(Default)]
+pub struct CqtBuilder<T> {
+    sr: Option<T>,
+    hop_length: Option<usize>,
+    fmin: Option<T>,
+    n_bins: Option<usize>,
+    bins_per_octave: Option<usize>,
+    tuning: Option<T>,
+    filter_scale: Option<T>,
+    gamma: Option<T>,
+    sparsity: Option<T>,
+    window: Option<Window>,
+    scale: Option<bool>,
+    hybrid: Option<bool>,
+}
+
+impl<T: StftNum> CqtBuilder<T> {
+    pub fn new() -> CqtBuilder<T> {
+        CqtBuilder {
+            sr: None,
+            hop_length: None,
+            fmin: None,
+            n_bins: None,
+            bins_per_octave: None,
+            tuning: None,
+            filter_scale: None,
+            gamma: None,
+            sparsity: None,
+            window: None,
+            scale: None,
+            hybrid: None,
+        }
+    }
+    /// Sampling rate of the signal, 22050 by default.
+    pub fn sr(mut self, sr: T) -> CqtBuilder<T> {
+        self.sr = Some(sr);
+        self
+    }
+    /// Must be divisible by `2^(n_octaves - 1)`, 512 by default.
+    pub fn hop_length(mut self, hop_length: usize) -> CqtBuilder<T> {
+        self.hop_length = Some(hop_length);
+        self
+    }
+    /// Center frequency of the lowest bin, C1 (32.70 Hz) by default.
+    pub fn fmin(mut self, fmin: T) -> CqtBuilder<T> {
+        self.fmin = Some(fmin);
+        self
+    }
+    pub fn n_bins(mut self, n_bins: usize) -> CqtBuilder<T> {
+        self.n_bins = Some(n_bins);
+        self
+    }
+    pub fn bins_per_octave(mut self, bins_per_octave: usize) -> CqtBuilder<T> {
+        self.bins_per_octave = Some(bins_per_octave);
+        self
+    }
+    /// Tuning offset in fractions of a bin.
+    pub fn tuning(mut self, tuning: T) -> CqtBuilder<T> {
+        self.tuning = Some(tuning);
+        self
+    }
+    /// Scales the filter lengths, values below 1 give a shorter time support.
+    pub fn filter_scale(mut self, filter_scale: T) -> CqtBuilder<T> {
+        self.filter_scale = Some(filter_scale);
+        self
+    }
+    /// Bandwidth offset in Hz of the variable-Q transform, 0 by default.
+    pub fn gamma(mut self, gamma: T) -> CqtBuilder<T> {
+        self.gamma = Some(gamma);
+        self
+    }
+    /// Fraction of the energy of every filter that is discarded, 0.01 by default.
+    pub fn sparsity(mut self, sparsity: T) -> CqtBuilder<T> {
+        self.sparsity = Some(sparsity);
+        self
+    }
+    pub fn window(mut self, window: Window) -> CqtBuilder<T> {
+        self.window = Some(window);
+        self
+    }
+    pub fn scale(mut self, scale: bool) -> CqtBuilder<T> {
+        self.scale = Some(scale);
+        self
+    }
+    /// Computes the bins with short filters from the STFT magnitude.
+    pub fn hybrid(mut self, hybrid: bool) -> CqtBuilder<T> {
+        self.hybrid = Some(hybrid);
+        self
+    }
+    pub fn build(self) -> Result<Cqt<T>> {
+        let sr = self.sr.unwrap_or_else(|| T::from(22050).unwrap());
+        let hop_length = self.hop_length.unwrap_or(512);
+        let fmin = self
+            .fmin
+            .unwrap_or_else(|| T::from(32.703_195_662_574_83).unwrap());
+        let n_bins = self.n_bins.unwrap_or(84);
+        let bins_per_octave = self.bins_per_octave.unwrap_or(12);
+        let tuning = self.tuning.unwrap_or_else(T::zero);
+        let filter_scale = self.filter_scale.unwrap_or_else(T::one);
+        let gamma = self.gamma.unwrap_or_else(T::zero);
+        let sparsity = self.sparsity.unwrap_or_else(|| T::from(0.01).unwrap());
+        let window = self.window.unwrap_or_default();
+        let scale = self.scale.unwrap_or(true);
+        let hybrid = self.hybrid.unwrap_or(false);
+        if hop_length == 0 {
+            return Err(From::from("CQT hop_length must be > 0"));
+        }
+        if sparsity < T::zero() || sparsity >= T::one() {
+            return Err(From::from("CQT sparsity must be in [0, 1)"));
+        }
+        if gamma < T::zero() {
+            return Err(From::from("CQT gamma must be >= 0"));
+        }
+        let params = FilterParams {
+            bins_per_octave,
+            window,
+            filter_scale,
+            gamma,
+            sparsity,
+        };
+        let two = T::from(2).unwrap();
+        let bpo = T::from(bins_per_octave).unwrap();
+        let fmin_tuned = fmin * two.powf(tuning / bpo);
+        let lengths = filters::constant_q_lengths(
+            sr,
+            fmin_tuned,
+            n_bins,
+            bins_per_octave,
+            window,
+            filter_scale,
+            gamma,
+        )?;
+
+        // Bins whose filters fit into two hops are computed from the STFT in the hybrid mode
+        let n_pseudo = if hybrid {
+            let limit = T::from(2 * hop_length).unwrap();
+            lengths
+                .iter()
+                .filter(|l| two.powf(l.log2().ceil()) < limit)
+                .count()
+        } else {
+            0
+        };
+        let n_full = n_bins - n_pseudo;
+        let pseudo = if n_pseudo > 0 {
+            let fmin_pseudo = fmin_tuned * two.powf(T::from(n_full).unwrap() / bpo);
+            Some(PseudoCqt::new(
+                sr,
+                fmin_pseudo,
+                n_pseudo,
+                hop_length,
+                params,
+                scale,
+            )?)
+        } else {
+            None
+        };
+        let (early_downsample, octaves) = if n_full > 0 {
+            plan_octaves(sr, fmin_tuned, n_full, hop_length, params)?
+        } else {
+            (None, Vec::new())
+        };
+
+        Ok(Cqt {
+            sr,
+            hop_length,
+            fmin,
+            n_bins,
+            bins_per_octave,
+            tuning,
+            filter_scale,
+            gamma,
+            sparsity,
+            scale,
+            hybrid,
+            lengths,
+            early_downsample,
+            octaves,
+            pseudo,
+        })
+    }
+}
+
+/// Optional early downsampling and the octaves from high to low frequencies.
+type OctavePlan<T> = (Option<Downsampler<T>>, Vec<Octave<T>>);
+
+/// Kernels and framing of the octaves from high to low frequencies.
+///
+/// The signal is downsampled at once if even the highest octave is far below Nyquist.
+fn plan_octaves<T: StftNum>(
+    sr: T,
+    fmin: T,
+    n_bins: usize,
+    hop_length: usize,
+    params: FilterParams<T>,
+) -> Result<OctavePlan<T>> {
+    let bins_per_octave = params.bins_per_octave;
+    let two = T::from(2).unwrap();
+    let bpo = T::from(bins_per_octave).unwrap();
+    let mut n_octaves = n_bins.div_ceil(bins_per_octave);
+    let n_filters = bins_per_octave.min(n_bins);
+    // Frequencies of the highest octave
+    let mut fmin_t = fmin * two.powf(T::from(n_bins - n_filters).unwrap() / bpo);
+    let fmax_t = fmin * two.powf(T::from(n_bins - 1).unwrap() / bpo);
+    let q = params.filter_scale / (two.powf(T::one() / bpo) - T::one());
+    let half_bandwidth = filters::window_bandwidth::<T>(params.window) / two;
+    let filter_cutoff = fmax_t * (T::one() + half_bandwidth / q) + params.gamma / two;
+    let nyquist = sr / two;
+    let fast = filter_cutoff < T::from(BW_FASTEST).unwrap() * nyquist;
+
+    let mut sr_t = sr;
+    let mut hop_t = hop_length;
+    let mut early_downsample = None;
+    if fast {
+        let count = early_downsample_count(nyquist, filter_cutoff, hop_length, n_octaves);
+        if count > 0 {
+            let factor = 1 << count;
+            hop_t /= factor;
+            sr_t = sr_t / T::from(factor).unwrap();
+            early_downsample = Some(Downsampler::new(factor));
+        }
+    }
+
+    let mut octaves = Vec::with_capacity(n_octaves);
+    if !fast {
+        // The highest octave is computed before any resampling, so that all downsampling can
+        // use the fast filter
+        let (kernel, n_fft) = fft_kernel(sr_t, fmin_t, n_filters, params, 0, T::one())?;
+        octaves.push(Octave {
+            downsample: false,
+            stft: octave_stft(n_fft, hop_t)?,
+            kernel,
+        });
+        fmin_t = fmin_t / two;
+        n_octaves -= 1;
+    }
+
+    if n_octaves > 1 && hop_t.trailing_zeros() as usize + 1 < n_octaves {
+        return Err(From::from(format!(
+            "hop_length={} must be a positive integer multiple of 2^{} for {}-octave CQT",
+            hop_length,
+            n_octaves - 1,
+            n_octaves
+        )));
+    }
+    let mut gain = T::one();
+    for i in 0..n_octaves {
+        if i > 0 {
+            // Together with the scale of the downsampled signal, every octave gains a
+            // factor of 2 to compensate the halved filter lengths
+            gain = gain * two.sqrt();
+            hop_t /= 2;
+            sr_t = sr_t / two;
+            fmin_t = fmin_t / two;
+        }
+        let (kernel, n_fft) = fft_kernel(sr_t, fmin_t, n_filters, params, 0, gain)?;
+        octaves.push(Octave {
+            downsample: i > 0,
+            stft: octave_stft(n_fft, hop_t)?,
+            kernel,
+        });
+    }
+    Ok((early_downsample, octaves))
+}
+
+/// Sparse FFT kernel of `n_filters` filters starting at `fmin` and the FFT length, which is
+/// at least `min_n_fft`.
+fn fft_kernel<T: StftNum>(
+    sr: T,
+    fmin: T,
+    n_filters: usize,
+    params: FilterParams<T>,
+    min_n_fft: usize,
+    gain: T,
+) -> Result<(SparseKernel<T>, usize)> {
+    let (basis, lengths) = filters::constant_q(
+        sr,
+        fmin,
+        n_filters,
+        params.bins_per_octave,
+        params.window,
+        params.filter_scale,
+        params.gamma,
+    )?;
+    let n_fft = basis.shape()[1].max(min_n_fft);
+    let n_freqs = n_fft / 2 + 1;
+    let mut planner = FFTplanner::new(false);
+    let fft = planner.plan_fft(n_fft);
+    let mut filter = vec![Complex::zero(); n_fft];
+    let mut spectrum = vec![Complex::zero(); n_fft];
+
+    let mut kernel = Vec::with_capacity(n_filters);
+    for (row, &length) in basis.outer_iter().zip(lengths.iter()) {
+        // Normalize with respect to the FFT length
+        let factor = length / T::from(n_fft).unwrap();
+        for (f, &v) in filter.iter_mut().zip(row.iter()) {
+            *f = v * factor;
+        }
+        for f in filter.iter_mut().skip(row.len()) {
+            *f = Complex::zero();
+        }
+        fft.process(&mut filter, &mut spectrum);
+        let row = &spectrum[..n_freqs];
+
+        // Drops the smallest bins that sum to less than `sparsity` of the L1 norm
+        let mut mags: Vec<T> = row.iter().map(|v| v.norm()).collect();
+        let norm = mags.iter().fold(T::zero(), |a, &b| a + b);
+        mags.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let mut cumulative = T::zero();
+        let mut threshold = mags[0];
+        for &m in mags.iter() {
+            cumulative = cumulative + m / norm;
+            threshold = m;
+            if cumulative >= params.sparsity {
+                break;
+            }
+        }
+        kernel.push(
+            row.iter()
+                .enumerate()
+                .filter(|(_, v)| v.norm() >= threshold)
+                .map(|(k, &v)| (k, v * gain))
+                .collect(),
+        );
+    }
+    Ok((kernel, n_fft))
+}
+
+fn octave_stft<T: StftNum>(n_fft: usize, hop_length: usize) -> Result<Stft<T>> {
+    StftBuilder::new()
+        .n_fft(n_fft)
+        .hop_length(hop_length)
+        .window(Array1::ones(n_fft))
+        .normalize(false)
+        .pad_mode(PadMode::Reflect)
+        .build()
+}
+
+/// Number of times the signal can be downsampled by two before the highest octave.
+fn early_downsample_count<T: StftNum>(
+    nyquist: T,
+    filter_cutoff: T,
+    hop_length: usize,
+    n_octaves: usize,
+) -> usize {
+    let ratio = T::from(BW_FASTEST).unwrap() * nyquist / filter_cutoff;
+    let count1 = ratio.log2().ceil().to_isize().unwrap() - 2;
+    let count2 = hop_length.trailing_zeros() as isize - n_octaves as isize + 1;
+    count1.min(count2).max(0) as usize
+}
+
+impl<T: StftNum> PseudoCqt<T> {
+    fn new(
+        sr: T,
+        fmin: T,
+        n_bins: usize,
+        hop_length: usize,
+        params: FilterParams<T>,
+        scale: bool,
+    ) -> Result<PseudoCqt<T>> {
+        // The FFT covers at least two hops
+        let min_n_fft = 2 * hop_length.next_power_of_two();
+        let (kernel, n_fft) = fft_kernel(sr, fmin, n_bins, params, min_n_fft, T::one())?;
+        let kernel = kernel
+            .into_iter()
+            .map(|filter| filter.into_iter().map(|(k, v)| (k, v.norm())).collect())
+            .collect();
+        let n_fft_t = T::from(n_fft).unwrap();
+        let norms = if scale {
+            vec![n_fft_t.sqrt().recip(); n_bins]
+        } else {
+            filters::constant_q_lengths(
+                sr,
+                fmin,
+                n_bins,
+                params.bins_per_octave,
+                params.window,
+                params.filter_scale,
+                params.gamma,
+            )?
+            .iter()
+            .map(|&l| (l / n_fft_t).sqrt())
+            .collect()
+        };
+        let stft = StftBuilder::new()
+            .n_fft(n_fft)
+            .hop_length(hop_length)
+            .normalize(false)
+            .pad_mode(PadMode::Reflect)
+            .build()?;
+        Ok(PseudoCqt {
+            stft,
+            kernel,
+            norms,
+        })
+    }
+}
+
+impl<T: StftNum + std::fmt::Debug + std::fmt::Display> PseudoCqt<T> {
+    fn process(&self, signal: ArrayView1<T>) -> Result<Array2<T>> {
+        let spec = self.stft.process_view(signal)?;
+        let mut response = Array2::<T>::zeros((self.kernel.len(), spec.dim().1));
+        for ((mut out, filter), &norm) in
+            response.outer_iter_mut().zip(&self.kernel).zip(&self.norms)
+        {
+            for &(k, v) in filter {
+                out.scaled_add(v * norm, &spec.row(k));
+            }
+        }
+        Ok(response)
+    }
+}
+
+impl<T: StftNum + std::fmt::Debug + std::fmt::Display> Cqt<T> {
+    /// Number of frames of the CQT of a signal with `signal_len` samples.
+    pub fn n_frames(&self, signal_len: usize) -> usize {
+        1 + signal_len / self.hop_length
+    }
+
+    /// Computes the complex CQT of shape `[n_bins, n_frames]`.
+    ///
+    /// Returns an error in the `hybrid` mode.
+    pub fn process_complex<'a, S>(&self, signal: S) -> Result<Array2<Complex<T>>>
+    where
+        S: AsArray<'a, T>,
+        T: 'a,
+    {
+        if self.pseudo.is_some() {
+            return Err(From::from("The hybrid CQT only computes magnitudes."));
+        }
+        self.process_octaves(signal.into())
+    }
+
+    /// Computes the CQT magnitude of shape `[n_bins, n_frames]`.
+    pub fn process<'a, S>(&self, signal: S) -> Result<Array2<T>>
+    where
+        S: AsArray<'a, T>,
+        T: 'a,
+    {
+        let signal = signal.into();
+        let low = self.process_octaves(signal)?.map(|v| v.norm());
+        let pseudo = match self.pseudo {
+            Some(ref pseudo) => pseudo,
+            None => return Ok(low),
+        };
+        let high = pseudo.process(signal)?;
+        let n_low = low.dim().0;
+        let n_frames = low.dim().1.min(high.dim().1);
+        let mut output = Array2::<T>::zeros((self.n_bins, n_frames).f());
+        output
+            .slice_mut(s![..n_low, ..])
+            .assign(&low.slice(s![.., ..n_frames]));
+        output
+            .slice_mut(s![n_low.., ..])
+            .assign(&high.slice(s![.., ..n_frames]));
+        Ok(output)
+    }
+
+    /// Complex response of the bins computed by the octaves.
+    fn process_octaves(&self, signal: ArrayView1<T>) -> Result<Array2<Complex<T>>> {
+        let mut y = match self.early_downsample {
+            Some(ref downsampler) => {
+                if signal.len() < downsampler.factor {
+                    return Err(From::from("Signal is too short for the CQT."));
+                }
+                let mut y = downsampler.process(signal);
+                if !self.scale {
+                    let gain = T::from(downsampler.factor).unwrap().sqrt();
+                    y.mapv_inplace(|v| v * gain);
+                }
+                y
+            }
+            None => signal.to_owned(),
+        };
+
+        let halve = Downsampler::new(2);
+        let mut responses = Vec::with_capacity(self.octaves.len());
+        for octave in &self.octaves {
+            if octave.downsample {
+                if y.len() < 2 {
+                    return Err(From::from("Signal is too short for the CQT."));
+                }
+                y = halve.process(y.view());
+            }
+            let spec = octave.stft.process_complex_view(&y)?;
+            let mut response = Array2::<Complex<T>>::zeros((octave.kernel.len(), spec.dim().1));
+            for (mut out, filter) in response.outer_iter_mut().zip(&octave.kernel) {
+                for &(k, v) in filter {
+                    out.scaled_add(v, &spec.row(k));
+                }
+            }
+            responses.push(response);
+        }
+
+        // Stacks the octaves from low to high and drops the unused bins of the lowest one
+        let n_bins = self.n_bins - self.pseudo.as_ref().map_or(0, |p| p.kernel.len());
+        let n_frames = responses
+            .iter()
+            .map(|r| r.dim().1)
+            .min()
+            .unwrap_or_else(|| self.n_frames(signal.len()));
+        let mut output = Array2::<Complex<T>>::zeros((n_bins, n_frames).f());
+        let rows = responses.iter().rev().flat_map(|r| r.outer_iter());
+        let n_rows: usize = responses.iter().map(|r| r.dim().0).sum();
+        for (mut out, row) in output.outer_iter_mut().zip(rows.skip(n_rows - n_bins)) {
+            out.assign(&row.slice(s![..n_frames]));
+        }
+        if self.scale {
+            // The early downsampling is compensated by the filter lengths at the reduced rate
+            let factor = match self.early_downsample {
+                Some(ref downsampler) => T::from(downsampler.factor).unwrap(),
+                None => T::one(),
+            };
+            for (mut out, &length) in output.outer_iter_mut().zip(self.lengths.iter()) {
+                let norm = (length / factor).sqrt();
+                out.mapv_inplace(|v| v / norm);
+            }
+        }
+        Ok(output)
+    }
+}
+
+/// Downsampling by an integer factor with the `kaiser_fast` filter of resampy.
+///
+/// The output has `ceil(len / factor)` samples and is scaled by `sqrt(factor)`, as done by
+/// `librosa.resample(..., scale=True)`.
+struct Downsampler<T> {
+    factor: usize,
+    // One-sided impulse response, taps[m] is the weight of the samples at offset +-m
+    taps: Vec<T>,
+}
+
+impl<T: StftNum> Downsampler<T> {
+    fn new(factor: usize) -> Downsampler<T> {
+        let num_zeros = 16;
+        let rolloff = 0.85;
+        let beta = 8.555_504_641_634_386;
+        let ratio = 1. / factor as f64;
+        let n_taps = num_zeros * factor;
+        let taps = (0..n_taps)
+            .map(|m| {
+                let x = m as f64 * ratio;
+                let sinc = if m == 0 {
+                    1.
+                } else {
+                    (PI * rolloff * x).sin() / (PI * rolloff * x)
+                };
+                let t = x / num_zeros as f64;
+                let kaiser = bessel_i0(beta * (1. - t * t).sqrt()) / bessel_i0(beta);
+                T::from(ratio * rolloff * sinc * kaiser).unwrap()
+            })
+            .collect();
+        Downsampler { factor, taps }
+    }
+
+    fn process(&self, signal: ArrayView1<T>) -> Array1<T> {
+        let len = signal.len() as isize;
+        let n_out = signal.len().div_ceil(self.factor);
+        // The last sample is zero padding if the length is not divisible by the factor
+        let n_valid = signal.len() / self.factor;
+        let scale = T::from(self.factor).unwrap().sqrt();
+        let n_taps = self.taps.len() as isize;
+        let mut output = Array1::zeros(n_out);
+        for (t, out) in output.iter_mut().take(n_valid).enumerate() {
+            let center = (t * self.factor) as isize;
+            let lo = (center - n_taps + 1).max(0);
+            let hi = (center + n_taps).min(len);
+            let mut acc = T::zero();
+            for i in lo..hi {
+                acc = acc + self.taps[(i - center).unsigned_abs()] * signal[i as usize];
+            }
+            *out = acc * scale;
+        }
+        output
+    }
+}
+
+/// Modified Bessel function of the first kind of order zero.
+fn bessel_i0(x: f64) -> f64 {
+    let y = x * x / 4.;
+    let mut term = 1.;
+    let mut sum = 1.;
+    let mut k = 1.;
+    while term > sum * 1e-17 {
+        term *= y / (k * k);
+        sum += term;
+        k += 1.;
+    }
+    sum
+}
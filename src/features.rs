@@ -0,0 +1,128 @@
+use ndarray::prelude::*;
+use std::f64::consts::PI;
+
+use crate::filters::{self, Norm};
+use crate::windows;
+use crate::StftNum;
+
+type Result<T> = ::std::result::Result<T, Box<::std::error::Error>>;
+
+/// Chromagram of shape `[n_chroma, n_frames]` from a spectrogram returned by
+/// `Stft::process()`.
+///
+/// The spectrogram of shape `[n_fft / 2 + 1, n_frames]` is projected with
+/// `filters::chroma()` using the librosa defaults and every frame is normalized by `norm`.
+/// `tuning` is the deviation from A440 in fractions of a chroma bin. Equal to
+/// `librosa.feature.chroma_stft` with `norm = Some(Norm::Max)` for a spectrogram with
+/// `OutputMode::Power(2.)`.
+pub fn chroma_stft<T: StftNum>(
+    spec: &Array2<T>,
+    sr: T,
+    n_fft: usize,
+    n_chroma: usize,
+    tuning: T,
+    norm: Option<Norm>,
+) -> Result<Array2<T>> {
+    if spec.shape()[0] != n_fft / 2 + 1 {
+        return Err(From::from(
+            "Spectrogram must have n_fft / 2 + 1 frequency bins.",
+        ));
+    }
+    let chromafb = filters::chroma(
+        sr,
+        n_fft,
+        n_chroma,
+        tuning,
+        T::from(5).unwrap(),
+        Some(T::from(2).unwrap()),
+        Some(Norm::L2),
+    )?;
+    let mut chroma = Array2::zeros((n_chroma, spec.shape()[1]).f());
+    chroma.assign(&chromafb.dot(spec));
+    if let Some(norm) = norm {
+        for frame in chroma.axis_iter_mut(Axis(1)) {
+            norm.normalize(frame);
+        }
+    }
+    Ok(chroma)
+}
+
+/// Chroma energy normalized statistics (CENS) of shape `[n_chroma, n_frames]` from a
+/// spectrogram returned by `Stft::process()`.
+///
+/// The L1 normalized chromagram of `chroma_stft()` is quantized to the levels 0.4, 0.2, 0.1
+/// and 0.05 and smoothed over time by a Hann window of `win_len_smooth` frames, 41 in librosa.
+/// Every frame is finally normalized by `norm`, `Norm::L2` in librosa. Equal to
+/// `librosa.feature.chroma_cens` computed on the same chromagram.
+pub fn chroma_cens<T: StftNum>(
+    spec: &Array2<T>,
+    sr: T,
+    n_fft: usize,
+    n_chroma: usize,
+    tuning: T,
+    win_len_smooth: Option<usize>,
+    norm: Option<Norm>,
+) -> Result<Array2<T>> {
+    let chroma = chroma_stft(spec, sr, n_fft, n_chroma, tuning, Some(Norm::L1))?;
+    let steps = [0.4, 0.2, 0.1, 0.05];
+    let quantized = chroma.mapv(|v| {
+        let level = steps.iter().filter(|&&s| v > T::from(s).unwrap()).count();
+        T::from(level as f64 / 4.).unwrap()
+    });
+
+    let mut cens = match win_len_smooth {
+        Some(win_len) if win_len > 0 => {
+            // Same-size convolution with zero padding like `scipy.ndimage.convolve`
+            let win = windows::hann::<T>(win_len + 2, true);
+            let win = &win / win.scalar_sum();
+            let n_frames = quantized.shape()[1];
+            let center = win.len() / 2;
+            let mut smoothed = Array2::zeros(quantized.dim().f());
+            for (mut out, row) in smoothed.outer_iter_mut().zip(quantized.outer_iter()) {
+                for (i, o) in out.iter_mut().enumerate() {
+                    for (k, &w) in win.iter().enumerate() {
+                        let j = (i + center).wrapping_sub(k);
+                        if j < n_frames {
+                            *o = *o + w * row[j];
+                        }
+                    }
+                }
+            }
+            smoothed
+        }
+        _ => quantized,
+    };
+    if let Some(norm) = norm {
+        for frame in cens.axis_iter_mut(Axis(1)) {
+            norm.normalize(frame);
+        }
+    }
+    Ok(cens)
+}
+
+/// Tonal centroid features (tonnetz) of shape `[6, n_frames]` from a spectrogram returned by
+/// `Stft::process()`.
+///
+/// The L1 normalized chromagram of `chroma_stft()` is projected onto the circles of fifths,
+/// minor thirds and major thirds, giving the coordinates of the tonal centroid in this order.
+/// Equal to `librosa.feature.tonnetz` computed on the same chromagram.
+pub fn tonnetz<T: StftNum>(
+    spec: &Array2<T>,
+    sr: T,
+    n_fft: usize,
+    n_chroma: usize,
+    tuning: T,
+) -> Result<Array2<T>> {
+    let chroma = chroma_stft(spec, sr, n_fft, n_chroma, tuning, Some(Norm::L1))?;
+    let scale = [7. / 6., 7. / 6., 1.5, 1.5, 2. / 3., 2. / 3.];
+    let radius = [1., 1., 1., 1., 0.5, 0.5];
+    let phi = Array2::from_shape_fn((6, n_chroma), |(p, c)| {
+        let pitch = 12. * c as f64 / n_chroma as f64;
+        // Even rows are the sines, odd rows the cosines
+        let offset = if p % 2 == 0 { 0.5 } else { 0. };
+        T::from(radius[p] * (PI * (scale[p] * pitch - offset)).cos()).unwrap()
+    });
+    let mut tonnetz = Array2::zeros((6, chroma.shape()[1]).f());
+    tonnetz.assign(&phi.dot(&chroma));
+    Ok(tonnetz)
+}
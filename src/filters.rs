@@ -1,20 +1,86 @@
+use std::f64::consts::PI;
+
 use ndarray::prelude::*;
 use num_traits::FromPrimitive;
+use rustfft::num_complex::Complex;
 
+use crate::windows::{self, Window};
 use crate::StftNum;
 
 type Result<T> = ::std::result::Result<T, Box<::std::error::Error>>;
 
-#[inline(always)]
-fn hz2mel<T: StftNum + FromPrimitive>(f: T) -> T {
-    T::from(2595.).unwrap() * (T::one() + f / T::from(700.).unwrap()).log10()
+/// Mel scale used by the mel filter banks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MelScale {
+    /// `2595 log10(1 + f / 700)` of the HTK toolkit
+    Htk,
+    /// Linear below 1 kHz and logarithmic above, from Slaney's Auditory Toolbox
+    Slaney,
 }
 
-#[inline(always)]
-fn _mel2hz<T: StftNum + FromPrimitive>(mel: T) -> T {
-    T::from(700.).unwrap() * (T::from(10.).unwrap().powf(mel / T::from(2595.).unwrap()) - T::one())
+impl Default for MelScale {
+    fn default() -> MelScale {
+        MelScale::Htk
+    }
 }
 
+impl MelScale {
+    /// Converts the frequency `f` in Hz to mels.
+    pub fn hz_to_mel<T: StftNum>(self, f: T) -> T {
+        match self {
+            MelScale::Htk => {
+                T::from(2595.).unwrap() * (T::one() + f / T::from(700.).unwrap()).log10()
+            }
+            MelScale::Slaney => {
+                let min_log_hz = T::from(1000).unwrap();
+                if f < min_log_hz {
+                    f / T::from(200. / 3.).unwrap()
+                } else {
+                    let logstep = T::from(6.4f64.ln() / 27.).unwrap();
+                    T::from(15).unwrap() + (f / min_log_hz).ln() / logstep
+                }
+            }
+        }
+    }
+
+    /// Converts `mel` to a frequency in Hz.
+    pub fn mel_to_hz<T: StftNum>(self, mel: T) -> T {
+        match self {
+            MelScale::Htk => {
+                T::from(700.).unwrap()
+                    * (T::from(10.).unwrap().powf(mel / T::from(2595.).unwrap()) - T::one())
+            }
+            MelScale::Slaney => {
+                let min_log_mel = T::from(15).unwrap();
+                if mel < min_log_mel {
+                    mel * T::from(200. / 3.).unwrap()
+                } else {
+                    let logstep = T::from(6.4f64.ln() / 27.).unwrap();
+                    T::from(1000).unwrap() * ((mel - min_log_mel) * logstep).exp()
+                }
+            }
+        }
+    }
+}
+
+/// Normalization of the filters of `mel_with()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MelNorm {
+    /// Divides every triangle by its width in Hz, giving a constant energy per channel
+    Slaney,
+    /// Unit sum of every filter
+    L1,
+    /// Unit Euclidean norm of every filter
+    L2,
+}
+
+/// HTK mel filter bank of shape `[n_fft / 2 + 1, n_mels]` without normalization.
+///
+/// Equal to `mel_with(sr, n_fft, n_mels, f_min, f_max, MelScale::Htk, None)`.
+///
+/// The filters are the columns, so a spectrogram `spec` of shape `[n_fft / 2 + 1, n_frames]`
+/// is projected with `mel.t().dot(&spec)`. Note that `chroma()` and `whisper::mel_filters()`
+/// use the transposed orientation of librosa and Whisper, with one filter per row.
 pub fn mel<T: StftNum + FromPrimitive + ::std::fmt::Debug>(
     sr: usize,
     n_fft: usize,
@@ -22,39 +88,272 @@ pub fn mel<T: StftNum + FromPrimitive + ::std::fmt::Debug>(
     f_min: Option<T>,
     f_max: Option<T>,
 ) -> Result<Array2<T>> {
-    let m_min: T = match f_min {
-        None => T::zero(),
-        Some(f) => hz2mel(f),
-    };
-    let m_max: T = hz2mel(f_max.unwrap_or_else(|| T::from(sr / 2).unwrap()));
-
-    // Mel points
-    let mut f_pts = Array::linspace(m_min, m_max, n_mels + 2);
-    // Frequency points
-    f_pts.mapv_inplace(_mel2hz);
-    // Convert to frequency bins
-    let bins = f_pts.mapv(|v| (T::from(n_fft).unwrap() * v / T::from(sr).unwrap()).floor());
+    mel_with(sr, n_fft, n_mels, f_min, f_max, MelScale::Htk, None)
+}
+
+/// Mel filter bank of shape `[n_fft / 2 + 1, n_mels]` on the mel `scale` with `norm`.
+///
+/// The `n_mels` triangular filters are spaced evenly on the mel `scale` between `f_min`
+/// (0 Hz by default) and `f_max` (Nyquist by default) and evaluated at the exact frequencies
+/// of the FFT bins. With `MelScale::Slaney` and `Some(MelNorm::Slaney)` this equals the
+/// transpose of the default `librosa.filters.mel`. The filters are the columns, like in
+/// `mel()`.
+pub fn mel_with<T: StftNum>(
+    sr: usize,
+    n_fft: usize,
+    n_mels: usize,
+    f_min: Option<T>,
+    f_max: Option<T>,
+    scale: MelScale,
+    norm: Option<MelNorm>,
+) -> Result<Array2<T>> {
+    let sr = T::from(sr).unwrap();
+    let f_min = f_min.unwrap_or_else(T::zero);
+    let f_max = f_max.unwrap_or_else(|| sr / T::from(2).unwrap());
+    if n_mels == 0 || f_min < T::zero() || f_min >= f_max {
+        return Err(From::from(
+            "Mel filters require n_mels > 0 and 0 <= f_min < f_max",
+        ));
+    }
+
+    // Corner frequencies of the triangles, evenly spaced in mels
+    let m_min = scale.hz_to_mel(f_min);
+    let m_max = scale.hz_to_mel(f_max);
+    let f_pts = Array::linspace(m_min, m_max, n_mels + 2).mapv(|m| scale.mel_to_hz(m));
 
     let mut fb = Array2::<T>::zeros((n_fft / 2 + 1, n_mels));
-    for m in 1..=n_mels {
-        let f_m_minus = bins[m - 1];
-        let f_m = bins[m];
-        let f_m_plus = bins[m + 1];
-        if f_m_minus != f_m {
-            fb.slice_mut(s![
-                f_m_minus.to_usize().unwrap()..f_m.to_usize().unwrap(),
-                m - 1,
-            ])
-            .assign(&((Array::range(f_m_minus, f_m, T::one()) - f_m_minus) / (f_m - f_m_minus)))
-        }
-        if f_m_plus != f_m {
-            fb.slice_mut(s![
-                f_m.to_usize().unwrap()..f_m_plus.to_usize().unwrap(),
-                m - 1,
-            ])
-            .assign(&((Array::range(-f_m, -f_m_plus, -T::one()) + f_m_plus) / (f_m_plus - f_m)))
+    for (m, mut filter) in fb.axis_iter_mut(Axis(1)).enumerate() {
+        let (left, center, right) = (f_pts[m], f_pts[m + 1], f_pts[m + 2]);
+        for (k, w) in filter.iter_mut().enumerate() {
+            let f = T::from(k).unwrap() * sr / T::from(n_fft).unwrap();
+            let lower = (f - left) / (center - left);
+            let upper = (right - f) / (right - center);
+            *w = lower.min(upper).max(T::zero());
+        }
+        match norm {
+            Some(MelNorm::Slaney) => {
+                let enorm = T::from(2).unwrap() / (right - left);
+                filter.mapv_inplace(|v| v * enorm);
+            }
+            Some(MelNorm::L1) => Norm::L1.normalize(filter),
+            Some(MelNorm::L2) => Norm::L2.normalize(filter),
+            None => {}
         }
     }
 
     Ok(fb)
 }
+
+/// Orthonormal type-II DCT matrix of shape `[n_filters, n_input]`.
+///
+/// Row `k` is `sqrt(2 / n_input) * cos(pi * k * (n + 0.5) / n_input)` with the first row
+/// scaled by `1 / sqrt(2)`, so that `dct.dot(x)` equals the first `n_filters` coefficients of
+/// `scipy.fftpack.dct(x, norm='ortho')`.
+pub fn dct<T: StftNum>(n_filters: usize, n_input: usize) -> Array2<T> {
+    let n = n_input as f64;
+    Array2::from_shape_fn((n_filters, n_input), |(k, i)| {
+        let scale = if k == 0 {
+            (1. / n).sqrt()
+        } else {
+            (2. / n).sqrt()
+        };
+        T::from(scale * (PI * k as f64 * (i as f64 + 0.5) / n).cos()).unwrap()
+    })
+}
+
+/// Equivalent noise bandwidth of `window` in FFT bins, matching
+/// `librosa.filters.window_bandwidth`.
+pub fn window_bandwidth<T: StftNum>(window: Window) -> T {
+    T::from(match window {
+        Window::Hann => 1.500_183_105_468_75,
+        Window::Hamming => 1.362_945_532_035_034_8,
+        Window::Boxcar => 1.0,
+    })
+    .unwrap()
+}
+
+/// Lengths in samples of the filters of a constant- or variable-Q filter bank.
+///
+/// Bin `k` has the center frequency `f_k = fmin * 2^(k / bins_per_octave)` and the
+/// length `Q * sr / (f_k + gamma / alpha)` with `alpha = 2^(1 / bins_per_octave) - 1` and
+/// `Q = filter_scale / alpha`. A bandwidth offset `gamma` in Hz shortens the low frequency
+/// filters, `gamma = 0` gives a constant Q. Returns an error if the pass-band of the highest
+/// filter lies beyond Nyquist.
+pub fn constant_q_lengths<T: StftNum>(
+    sr: T,
+    fmin: T,
+    n_bins: usize,
+    bins_per_octave: usize,
+    window: Window,
+    filter_scale: T,
+    gamma: T,
+) -> Result<Array1<T>> {
+    if n_bins == 0 || bins_per_octave == 0 {
+        return Err(From::from("n_bins and bins_per_octave must be > 0"));
+    }
+    let two = T::from(2).unwrap();
+    let bpo = T::from(bins_per_octave).unwrap();
+    let alpha = two.powf(T::one() / bpo) - T::one();
+    let q = filter_scale / alpha;
+    let freqs = Array1::from_shape_fn(n_bins, |k| fmin * two.powf(T::from(k).unwrap() / bpo));
+    let half_bandwidth = window_bandwidth::<T>(window) / two;
+    if freqs[n_bins - 1] * (T::one() + half_bandwidth / q) + gamma / two > sr / two {
+        return Err(From::from("Filter pass-band lies beyond Nyquist"));
+    }
+    Ok(freqs.mapv(|f| q * sr / (f + gamma / alpha)))
+}
+
+/// Time-domain constant-Q filter bank of shape `[n_bins, n_fft]` and the filter lengths.
+///
+/// Every filter is a complex exponential at the center frequency of its bin, windowed by a
+/// periodic `window` of the filter length and normalized to unit L1 norm. The filters are
+/// centered in `n_fft`, the smallest power of two that fits the longest filter. Follows
+/// `librosa.filters.constant_q`, see `constant_q_lengths()` for the parameters.
+pub fn constant_q<T: StftNum>(
+    sr: T,
+    fmin: T,
+    n_bins: usize,
+    bins_per_octave: usize,
+    window: Window,
+    filter_scale: T,
+    gamma: T,
+) -> Result<(Array2<Complex<T>>, Array1<T>)> {
+    let lengths = constant_q_lengths(
+        sr,
+        fmin,
+        n_bins,
+        bins_per_octave,
+        window,
+        filter_scale,
+        gamma,
+    )?;
+    let two = T::from(2).unwrap();
+    let bpo = T::from(bins_per_octave).unwrap();
+    let max_len = lengths.fold(T::zero(), |a, &b| a.max(b));
+    let n_fft = two.powf(max_len.log2().ceil()).to_usize().unwrap();
+
+    let two_pi = T::from(2. * PI).unwrap();
+    let mut basis = Array2::<Complex<T>>::zeros((n_bins, n_fft));
+    for (k, (mut row, &length)) in basis.outer_iter_mut().zip(lengths.iter()).enumerate() {
+        let freq = fmin * two.powf(T::from(k).unwrap() / bpo);
+        // Sample indices floor(-l / 2)..floor(l / 2) around the filter center
+        let start = (-length / two).floor();
+        let len = ((length / two).floor() - start).to_usize().unwrap();
+        let win = windows::get_window::<T>(window, len, true);
+        let filter: Vec<Complex<T>> = win
+            .iter()
+            .enumerate()
+            .map(|(n, &w)| {
+                let phi = (start + T::from(n).unwrap()) * two_pi * freq / sr;
+                Complex::new(phi.cos(), phi.sin()) * w
+            })
+            .collect();
+        let norm = filter.iter().fold(T::zero(), |a, v| a + v.norm());
+        let offset = (n_fft - len) / 2;
+        for (b, v) in row
+            .slice_mut(s![offset..offset + len])
+            .iter_mut()
+            .zip(filter)
+        {
+            *b = v / norm;
+        }
+    }
+    Ok((basis, lengths))
+}
+
+/// Normalization of the columns of a filter bank or feature matrix.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Norm {
+    /// Sum of the absolute values.
+    L1,
+    /// Euclidean norm.
+    L2,
+    /// Maximum absolute value.
+    Max,
+}
+
+impl Norm {
+    /// Scales `x` to unit norm. Vectors with a norm below the smallest positive value of `T`
+    /// are left unchanged.
+    pub(crate) fn normalize<T: StftNum>(self, mut x: ArrayViewMut1<T>) {
+        let norm = match self {
+            Norm::L1 => x.fold(T::zero(), |a, b| a + b.abs()),
+            Norm::L2 => x.fold(T::zero(), |a, b| a + *b * *b).sqrt(),
+            Norm::Max => x.fold(T::zero(), |a, b| a.max(b.abs())),
+        };
+        if norm >= T::min_positive_value() {
+            x.mapv_inplace(|v| v / norm);
+        }
+    }
+}
+
+/// Chroma filter bank of shape `[n_chroma, n_fft / 2 + 1]` that maps STFT bins to pitch
+/// classes, starting at C.
+///
+/// Every bin contributes to the pitch classes by a Gaussian bump around its pitch, whose width
+/// grows with the bin spacing at low frequencies. `tuning` shifts the reference A440 by a
+/// fraction of a chroma bin. With `octwidth`, the bins are weighted by a Gaussian of that width
+/// in octaves around the octave `ctroct` above A0 (27.5 Hz). `norm` normalizes every column
+/// before the weighting. Follows `librosa.filters.chroma`, which uses `ctroct = 5`,
+/// `octwidth = Some(2)` and `Norm::L2` by default.
+///
+/// Unlike `mel()`, the filters are the rows, as in librosa, so a spectrogram `spec` of shape
+/// `[n_fft / 2 + 1, n_frames]` is projected with `chroma.dot(&spec)`.
+pub fn chroma<T: StftNum>(
+    sr: T,
+    n_fft: usize,
+    n_chroma: usize,
+    tuning: T,
+    ctroct: T,
+    octwidth: Option<T>,
+    norm: Option<Norm>,
+) -> Result<Array2<T>> {
+    if n_chroma == 0 || n_fft < 2 {
+        return Err(From::from("chroma requires n_chroma > 0 and n_fft >= 2"));
+    }
+    let n = T::from(n_chroma).unwrap();
+    let a440 = T::from(440).unwrap() * T::from(2).unwrap().powf(tuning / n);
+    // Pitch of every bin in chroma bins above A0
+    let mut frqbins: Vec<T> = (1..n_fft)
+        .map(|k| {
+            let freq = T::from(k).unwrap() * sr / T::from(n_fft).unwrap();
+            n * (freq / (a440 / T::from(16).unwrap())).log2()
+        })
+        .collect();
+    // The DC bin is placed 1.5 octaves below the first bin
+    frqbins.insert(0, frqbins[0] - T::from(1.5).unwrap() * n);
+    let mut binwidths: Vec<T> = frqbins
+        .windows(2)
+        .map(|w| (w[1] - w[0]).max(T::one()))
+        .collect();
+    binwidths.push(T::one());
+
+    let half = (n / T::from(2).unwrap()).round();
+    let offset = half + T::from(10).unwrap() * n;
+    let mut wts = Array2::<T>::from_shape_fn((n_chroma, n_fft), |(c, k)| {
+        // Distance to the pitch class in the range -n_chroma / 2 .. n_chroma / 2
+        let d = frqbins[k] - T::from(c).unwrap() + offset;
+        let d = d - (d / n).floor() * n - half;
+        (-T::from(0.5).unwrap() * (T::from(2).unwrap() * d / binwidths[k]).powi(2)).exp()
+    });
+    if let Some(norm) = norm {
+        for column in wts.axis_iter_mut(Axis(1)) {
+            norm.normalize(column);
+        }
+    }
+    if let Some(octwidth) = octwidth {
+        for (mut column, &f) in wts.axis_iter_mut(Axis(1)).zip(&frqbins) {
+            let weight = (-T::from(0.5).unwrap() * ((f / n - ctroct) / octwidth).powi(2)).exp();
+            column.mapv_inplace(|v| v * weight);
+        }
+    }
+    // Starts at C instead of A, which is 3 semitones above
+    let shift = (3 * (n_chroma / 12)) % n_chroma;
+    let mut rolled = Array2::<T>::zeros((n_chroma, n_fft / 2 + 1));
+    for c in 0..n_chroma {
+        rolled
+            .row_mut(c)
+            .assign(&wts.slice(s![(c + shift) % n_chroma, ..n_fft / 2 + 1]));
+    }
+    Ok(rolled)
+}
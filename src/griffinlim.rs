@@ -0,0 +1,162 @@
+use std::f64::consts::PI;
+
+use ndarray::prelude::*;
+use ndarray::Zip;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rustfft::num_complex::Complex;
+
+use crate::spectrum::Stft;
+use crate::StftNum;
+
+type Result<T> = ::std::result::Result<T, Box<::std::error::Error>>;
+
+/// Initial phase estimate of the Griffin-Lim iterations.
+pub enum PhaseInit {
+    /// Uniformly distributed random phase
+    Random,
+    /// Zero phase
+    Zeros,
+}
+
+impl Default for PhaseInit {
+    fn default() -> PhaseInit {
+        PhaseInit::Random
+    }
+}
+
+/// Phase reconstruction of magnitude spectrograms using the (fast) Griffin-Lim algorithm.
+///
+/// With `momentum == 0` this is the original algorithm of Griffin and Lim, otherwise the fast
+/// variant of Perraudin et al. is used.
+pub struct GriffinLim<T> {
+    pub n_iter: usize,
+    pub momentum: T,
+    pub init: PhaseInit,
+    /// Seed of the random initial phase, drawn from the thread RNG if `None`.
+    pub seed: Option<u64>,
+    pub length: Option<usize>,
+}
+
+#[derive(Default)]
+pub struct GriffinLimBuilder<T> {
+    n_iter: Option<usize>,
+    momentum: Option<T>,
+    init: Option<PhaseInit>,
+    seed: Option<u64>,
+    length: Option<usize>,
+}
+
+impl<T: StftNum> GriffinLimBuilder<T> {
+    pub fn new() -> GriffinLimBuilder<T> {
+        GriffinLimBuilder {
+            n_iter: None,
+            momentum: None,
+            init: None,
+            seed: None,
+            length: None,
+        }
+    }
+    pub fn n_iter(mut self, n_iter: usize) -> GriffinLimBuilder<T> {
+        self.n_iter = Some(n_iter);
+        self
+    }
+    pub fn momentum(mut self, momentum: T) -> GriffinLimBuilder<T> {
+        self.momentum = Some(momentum);
+        self
+    }
+    pub fn init(mut self, init: PhaseInit) -> GriffinLimBuilder<T> {
+        self.init = Some(init);
+        self
+    }
+    /// Seeds the random initial phase to make the reconstruction reproducible.
+    pub fn seed(mut self, seed: u64) -> GriffinLimBuilder<T> {
+        self.seed = Some(seed);
+        self
+    }
+    /// Length of the reconstructed signal. Required for `PadMode::Center`.
+    pub fn length(mut self, length: usize) -> GriffinLimBuilder<T> {
+        self.length = Some(length);
+        self
+    }
+    pub fn build(self) -> Result<GriffinLim<T>> {
+        let momentum = self.momentum.unwrap_or_else(|| T::from(0.99).unwrap());
+        if momentum < T::zero() {
+            return Err(From::from("Griffin-Lim momentum must be >= 0"));
+        }
+        Ok(GriffinLim {
+            n_iter: self.n_iter.unwrap_or(32),
+            momentum,
+            init: self.init.unwrap_or_default(),
+            seed: self.seed,
+            length: self.length,
+        })
+    }
+}
+
+impl<T: StftNum + std::fmt::Debug + std::fmt::Display> GriffinLim<T> {
+    /// Reconstructs a signal from `magnitude`, a spectrogram as returned by `stft.process()`
+    /// with `OutputMode::Magnitude`.
+    ///
+    /// Returns the signal and the spectral convergence `‖|STFT(x)| - S‖ / ‖S‖` of every
+    /// iteration.
+    pub fn process(&self, stft: &Stft<T>, magnitude: &Array2<T>) -> Result<(Vec<T>, Vec<T>)> {
+        let n_frames = magnitude.shape()[1];
+        let mut angles = match self.init {
+            PhaseInit::Random => {
+                let mut rng = match self.seed {
+                    Some(seed) => StdRng::seed_from_u64(seed),
+                    None => StdRng::from_rng(rand::thread_rng())?,
+                };
+                let two_pi = T::from(2. * PI).unwrap();
+                Array2::from_shape_fn(magnitude.dim(), |_| {
+                    Complex::from_polar(&T::one(), &(two_pi * T::from(rng.gen::<f64>()).unwrap()))
+                })
+            }
+            PhaseInit::Zeros => {
+                Array2::from_elem(magnitude.dim(), Complex::new(T::one(), T::zero()))
+            }
+        };
+        let mut rebuilt = Array2::<Complex<T>>::zeros(magnitude.dim());
+        let mut spec = Array2::<Complex<T>>::zeros(magnitude.dim());
+        let mut convergence = Vec::with_capacity(self.n_iter);
+
+        let alpha = self.momentum / (T::one() + self.momentum);
+        let eps = T::from(1e-16).unwrap();
+        let norm = magnitude.map(|v| v.powi(2)).scalar_sum().sqrt();
+        for _ in 0..self.n_iter {
+            // Project onto the set of consistent spectrograms
+            Zip::from(&mut spec)
+                .and(magnitude)
+                .and(&angles)
+                .apply(|s, &m, &a| *s = a * m);
+            let signal = stft.istft(&spec, self.length)?;
+            let tprev = rebuilt;
+            rebuilt = stft.process_complex(signal)?;
+            if rebuilt.shape()[1] < n_frames {
+                return Err(From::from("Griffin-Lim reconstruction is too short."));
+            }
+            rebuilt.slice_collapse(s![.., ..n_frames]);
+
+            let mut error = T::zero();
+            Zip::from(&rebuilt)
+                .and(magnitude)
+                .apply(|r, &m| error = error + (r.norm() - m).powi(2));
+            convergence.push(error.sqrt() / norm);
+
+            // Update the phase, extrapolated by the previous estimate for the fast variant
+            Zip::from(&mut angles)
+                .and(&rebuilt)
+                .and(&tprev)
+                .apply(|a, &r, &p| {
+                    let v = r - p * alpha;
+                    *a = v / (v.norm() + eps);
+                });
+        }
+        Zip::from(&mut spec)
+            .and(magnitude)
+            .and(&angles)
+            .apply(|s, &m, &a| *s = a * m);
+        Ok((stft.istft(&spec, self.length)?, convergence))
+    }
+}
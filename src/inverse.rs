@@ -0,0 +1,178 @@
+use ndarray::prelude::*;
+
+use crate::griffinlim::GriffinLim;
+use crate::spectrum::Stft;
+use crate::StftNum;
+
+type Result<T> = ::std::result::Result<T, Box<::std::error::Error>>;
+
+// Iterations and relative tolerance of the non-negative least squares refinement
+const NNLS_MAX_ITER: usize = 500;
+const NNLS_TOL: f64 = 1e-6;
+
+/// Approximates the STFT magnitude of shape `[n_fft / 2 + 1, n_frames]` from a mel
+/// spectrogram of shape `[n_mels, n_frames]`.
+///
+/// `mel_basis` is the filter bank of `filters::mel_with()` that computed `mel` from `|X|^power`.
+/// Every frame is the non-negative least squares solution of `mel_basis^T x = mel`, starting
+/// from the minimum norm solution of the pseudo-inverse. Frames where the pseudo-inverse is
+/// non-negative are exact and returned directly, the others are refined by an accelerated
+/// projected gradient descent. Like `librosa.feature.inverse.mel_to_stft`, which finds another
+/// solution when the system is underdetermined.
+pub fn mel_to_stft<T: StftNum>(
+    mel: &Array2<T>,
+    mel_basis: &Array2<T>,
+    power: T,
+) -> Result<Array2<T>> {
+    let (n_freqs, n_mels) = mel_basis.dim();
+    if mel.shape()[0] != n_mels {
+        return Err(From::from(
+            "Mel spectrogram must have n_mels rows of the mel basis.",
+        ));
+    }
+    if power <= T::zero() {
+        return Err(From::from("Power must be > 0"));
+    }
+    let n_frames = mel.shape()[1];
+
+    // Pseudo-inverse mel_basis (mel_basis^T mel_basis)^-1 of the full rank filter bank
+    let gram = mel_basis.t().dot(mel_basis);
+    let mut stft = mel_basis.dot(&solve_spd(&gram, mel)?);
+    let negative: Vec<usize> = (0..n_frames)
+        .filter(|&t| stft.column(t).iter().any(|&v| v < T::zero()))
+        .collect();
+    stft.mapv_inplace(|v| v.max(T::zero()));
+
+    if !negative.is_empty() {
+        let target = mel.select(Axis(1), &negative);
+        let init = stft.select(Axis(1), &negative);
+        let refined = nnls(mel_basis, &gram, &target, init);
+        for (i, &t) in negative.iter().enumerate() {
+            stft.column_mut(t).assign(&refined.column(i));
+        }
+    }
+    let exponent = T::one() / power;
+    stft.mapv_inplace(|v| v.powf(exponent));
+
+    let mut output = Array2::zeros((n_freqs, n_frames).f());
+    output.assign(&stft);
+    Ok(output)
+}
+
+/// Reconstructs a signal from a mel spectrogram of shape `[n_mels, n_frames]`.
+///
+/// The STFT magnitude is estimated by `mel_to_stft()` and its phase by `griffin_lim` with the
+/// framing of `stft`, like `librosa.feature.inverse.mel_to_audio`.
+pub fn mel_to_audio<T>(
+    mel: &Array2<T>,
+    mel_basis: &Array2<T>,
+    power: T,
+    stft: &Stft<T>,
+    griffin_lim: &GriffinLim<T>,
+) -> Result<Vec<T>>
+where
+    T: StftNum + std::fmt::Debug + std::fmt::Display,
+{
+    if mel_basis.shape()[0] != stft.n_fft / 2 + 1 {
+        return Err(From::from(
+            "Mel basis must have n_fft / 2 + 1 frequency bins.",
+        ));
+    }
+    let magnitude = mel_to_stft(mel, mel_basis, power)?;
+    let (signal, _) = griffin_lim.process(stft, &magnitude)?;
+    Ok(signal)
+}
+
+/// Solves `gram x = b` for the symmetric positive definite `gram` by a Cholesky decomposition.
+///
+/// A small ridge keeps the decomposition defined for the empty filters of a rank deficient
+/// filter bank.
+fn solve_spd<T: StftNum>(gram: &Array2<T>, b: &Array2<T>) -> Result<Array2<T>> {
+    let n = gram.shape()[0];
+    let ridge = gram.diag().fold(T::zero(), |a, &v| a.max(v)) * T::from(1e-10).unwrap();
+    let mut l = Array2::<T>::zeros((n, n));
+    for i in 0..n {
+        for j in 0..=i {
+            let mut sum = gram[[i, j]];
+            for k in 0..j {
+                sum = sum - l[[i, k]] * l[[j, k]];
+            }
+            if i == j {
+                let d = sum + ridge;
+                if d <= T::zero() {
+                    return Err(From::from("Mel basis is not positive definite"));
+                }
+                l[[i, i]] = d.sqrt();
+            } else {
+                l[[i, j]] = sum / l[[j, j]];
+            }
+        }
+    }
+
+    // Forward and back substitution for all columns of b
+    let mut x = b.to_owned();
+    for mut column in x.axis_iter_mut(Axis(1)) {
+        for i in 0..n {
+            let mut sum = column[i];
+            for k in 0..i {
+                sum = sum - l[[i, k]] * column[k];
+            }
+            column[i] = sum / l[[i, i]];
+        }
+        for i in (0..n).rev() {
+            let mut sum = column[i];
+            for k in i + 1..n {
+                sum = sum - l[[k, i]] * column[k];
+            }
+            column[i] = sum / l[[i, i]];
+        }
+    }
+    Ok(x)
+}
+
+/// Minimizes `|basis^T x - b|^2` subject to `x >= 0` for every column of `b` by FISTA, the
+/// accelerated projected gradient descent of Beck and Teboulle.
+fn nnls<T: StftNum>(
+    basis: &Array2<T>,
+    gram: &Array2<T>,
+    b: &Array2<T>,
+    init: Array2<T>,
+) -> Array2<T> {
+    // Lipschitz constant of the gradient, the largest eigenvalue of the Gram matrix
+    let mut v = Array1::<T>::ones(gram.shape()[0]);
+    let mut lipschitz = T::zero();
+    for _ in 0..100 {
+        let w = gram.dot(&v);
+        lipschitz = w.dot(&w).sqrt();
+        if lipschitz <= T::zero() {
+            return init;
+        }
+        v = w / lipschitz;
+    }
+    let step = T::one() / lipschitz;
+    let tol = T::from(NNLS_TOL).unwrap();
+
+    let mut x = init;
+    let mut y = x.clone();
+    let mut momentum = T::one();
+    for _ in 0..NNLS_MAX_ITER {
+        let residual = basis.t().dot(&y) - b;
+        let gradient = basis.dot(&residual);
+        let x_next = (&y - &(gradient * step)).mapv(|v| v.max(T::zero()));
+
+        let momentum_next = (T::one()
+            + (T::one() + T::from(4).unwrap() * momentum * momentum).sqrt())
+            / T::from(2).unwrap();
+        let delta = &x_next - &x;
+        y = &x_next + &(&delta * ((momentum - T::one()) / momentum_next));
+        momentum = momentum_next;
+
+        let change = delta.fold(T::zero(), |a, &d| a + d * d).sqrt();
+        let norm = x_next.fold(T::zero(), |a, &v| a + v * v).sqrt();
+        x = x_next;
+        if change <= tol * norm {
+            break;
+        }
+    }
+    x
+}
@@ -0,0 +1,538 @@
+use std::f64::consts::PI;
+
+use ndarray::prelude::*;
+use ndarray::AsArray;
+use rand::distributions::{Distribution, Normal};
+use rustfft::num_complex::Complex;
+
+use crate::filters;
+use crate::realfft::RealFft;
+use crate::StftNum;
+
+type Result<T> = ::std::result::Result<T, Box<::std::error::Error>>;
+
+/// Window functions of Kaldi's frame extraction.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WindowType {
+    /// Symmetric Hamming window
+    Hamming,
+    /// Symmetric Hann window
+    Hanning,
+    /// Symmetric Hann window raised to the power 0.85
+    Povey,
+    Rectangular,
+    /// Symmetric Blackman window with the coefficient `blackman_coeff`
+    Blackman,
+}
+
+impl Default for WindowType {
+    fn default() -> WindowType {
+        WindowType::Povey
+    }
+}
+
+/// Framing of the waveform, mirrors Kaldi's `FrameExtractionOptions`.
+///
+/// `torchaudio.compliance.kaldi` uses the same defaults except for `dither = 0`.
+#[derive(Clone, Debug)]
+pub struct FrameExtractionOptions<T> {
+    pub sample_frequency: T,
+    /// Frame shift in milliseconds.
+    pub frame_shift_ms: T,
+    /// Frame length in milliseconds.
+    pub frame_length_ms: T,
+    /// Standard deviation of the Gaussian noise added to every frame, 0 disables dithering.
+    pub dither: T,
+    /// Pre-emphasis `x[i] - preemph_coeff * x[i - 1]` applied to every frame.
+    pub preemph_coeff: T,
+    /// Subtract the mean of every frame.
+    pub remove_dc_offset: bool,
+    pub window_type: WindowType,
+    /// Zero-pad every frame to the next power of two for the FFT.
+    pub round_to_power_of_two: bool,
+    pub blackman_coeff: T,
+    /// Only output frames that fit completely in the waveform. Otherwise the number of frames
+    /// is `(n + frame_shift / 2) / frame_shift` and the waveform is reflected at its edges.
+    pub snip_edges: bool,
+}
+
+impl<T: StftNum> Default for FrameExtractionOptions<T> {
+    fn default() -> FrameExtractionOptions<T> {
+        FrameExtractionOptions {
+            sample_frequency: T::from(16000).unwrap(),
+            frame_shift_ms: T::from(10).unwrap(),
+            frame_length_ms: T::from(25).unwrap(),
+            dither: T::one(),
+            preemph_coeff: T::from(0.97).unwrap(),
+            remove_dc_offset: true,
+            window_type: WindowType::default(),
+            round_to_power_of_two: true,
+            blackman_coeff: T::from(0.42).unwrap(),
+            snip_edges: true,
+        }
+    }
+}
+
+impl<T: StftNum> FrameExtractionOptions<T> {
+    /// Frame shift in samples.
+    pub fn window_shift(&self) -> usize {
+        (self.sample_frequency * self.frame_shift_ms * T::from(0.001).unwrap())
+            .to_usize()
+            .unwrap_or(0)
+    }
+
+    /// Frame length in samples.
+    pub fn window_size(&self) -> usize {
+        (self.sample_frequency * self.frame_length_ms * T::from(0.001).unwrap())
+            .to_usize()
+            .unwrap_or(0)
+    }
+
+    /// Length of the zero-padded frames, the FFT size.
+    pub fn padded_window_size(&self) -> usize {
+        if self.round_to_power_of_two {
+            self.window_size().next_power_of_two()
+        } else {
+            self.window_size()
+        }
+    }
+
+    /// Number of frames of a waveform of `num_samples` samples.
+    pub fn num_frames(&self, num_samples: usize) -> usize {
+        let (size, shift) = (self.window_size(), self.window_shift());
+        if self.snip_edges {
+            if num_samples < size {
+                0
+            } else {
+                1 + (num_samples - size) / shift
+            }
+        } else {
+            (num_samples + shift / 2) / shift
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.sample_frequency <= T::zero() {
+            return Err(From::from("Kaldi sample_frequency must be > 0"));
+        }
+        if self.window_shift() == 0 {
+            return Err(From::from("Kaldi frame shift must be at least one sample"));
+        }
+        if self.window_size() < 2 {
+            return Err(From::from(
+                "Kaldi frame length must be at least two samples",
+            ));
+        }
+        if self.padded_window_size() % 2 != 0 {
+            return Err(From::from("Kaldi padded frame length must be even"));
+        }
+        if self.preemph_coeff < T::zero() || self.preemph_coeff > T::one() {
+            return Err(From::from("Kaldi preemph_coeff must be in [0, 1]"));
+        }
+        if self.dither < T::zero() {
+            return Err(From::from("Kaldi dither must be >= 0"));
+        }
+        Ok(())
+    }
+
+    fn window(&self) -> Array1<T> {
+        let size = self.window_size();
+        let a = 2. * PI / (size - 1) as f64;
+        let blackman = self.blackman_coeff.to_f64().unwrap();
+        Array1::from_shape_fn(size, |i| {
+            let i = i as f64;
+            let hann = 0.5 - 0.5 * (a * i).cos();
+            T::from(match self.window_type {
+                WindowType::Hamming => 0.54 - 0.46 * (a * i).cos(),
+                WindowType::Hanning => hann,
+                WindowType::Povey => hann.powf(0.85),
+                WindowType::Rectangular => 1.,
+                WindowType::Blackman => {
+                    blackman - 0.5 * (a * i).cos() + (0.5 - blackman) * (2. * a * i).cos()
+                }
+            })
+            .unwrap()
+        })
+    }
+}
+
+/// Triangular mel filter bank, mirrors Kaldi's `MelBanksOptions` without VTLN warping.
+#[derive(Clone, Debug)]
+pub struct MelBanksOptions<T> {
+    pub num_bins: usize,
+    /// Lowest frequency of the filter bank in Hz.
+    pub low_freq: T,
+    /// Highest frequency in Hz, values `<= 0` are offsets from Nyquist.
+    pub high_freq: T,
+}
+
+impl<T: StftNum> Default for MelBanksOptions<T> {
+    fn default() -> MelBanksOptions<T> {
+        MelBanksOptions {
+            num_bins: 23,
+            low_freq: T::from(20).unwrap(),
+            high_freq: T::zero(),
+        }
+    }
+}
+
+/// Options of `spectrogram()`, mirrors Kaldi's `SpectrogramOptions`.
+#[derive(Clone, Debug)]
+pub struct SpectrogramOptions<T> {
+    pub frame_opts: FrameExtractionOptions<T>,
+    /// Floor of the energy, 0 disables the floor. `torchaudio.compliance.kaldi` uses 1.
+    pub energy_floor: T,
+    /// Compute the energy before pre-emphasis and windowing.
+    pub raw_energy: bool,
+}
+
+impl<T: StftNum> Default for SpectrogramOptions<T> {
+    fn default() -> SpectrogramOptions<T> {
+        SpectrogramOptions {
+            frame_opts: FrameExtractionOptions::default(),
+            energy_floor: T::zero(),
+            raw_energy: true,
+        }
+    }
+}
+
+/// Options of `fbank()`, mirrors Kaldi's `FbankOptions`.
+#[derive(Clone, Debug)]
+pub struct FbankOptions<T> {
+    pub frame_opts: FrameExtractionOptions<T>,
+    pub mel_opts: MelBanksOptions<T>,
+    /// Add the log energy of the frame as an extra feature.
+    pub use_energy: bool,
+    pub energy_floor: T,
+    pub raw_energy: bool,
+    /// Put the energy last instead of first.
+    pub htk_compat: bool,
+    pub use_log_fbank: bool,
+    /// Use the power instead of the magnitude spectrum.
+    pub use_power: bool,
+}
+
+impl<T: StftNum> Default for FbankOptions<T> {
+    fn default() -> FbankOptions<T> {
+        FbankOptions {
+            frame_opts: FrameExtractionOptions::default(),
+            mel_opts: MelBanksOptions::default(),
+            use_energy: false,
+            energy_floor: T::zero(),
+            raw_energy: true,
+            htk_compat: false,
+            use_log_fbank: true,
+            use_power: true,
+        }
+    }
+}
+
+/// Options of `mfcc()`, mirrors Kaldi's `MfccOptions`.
+#[derive(Clone, Debug)]
+pub struct MfccOptions<T> {
+    pub frame_opts: FrameExtractionOptions<T>,
+    pub mel_opts: MelBanksOptions<T>,
+    pub num_ceps: usize,
+    /// Replace `c0` by the log energy of the frame.
+    pub use_energy: bool,
+    pub energy_floor: T,
+    pub raw_energy: bool,
+    /// Cepstral liftering coefficient, 0 disables liftering.
+    pub cepstral_lifter: T,
+    /// Put `c0` or the energy last, `c0` is then scaled by `sqrt(2)`.
+    pub htk_compat: bool,
+}
+
+impl<T: StftNum> Default for MfccOptions<T> {
+    fn default() -> MfccOptions<T> {
+        MfccOptions {
+            frame_opts: FrameExtractionOptions::default(),
+            mel_opts: MelBanksOptions::default(),
+            num_ceps: 13,
+            use_energy: true,
+            energy_floor: T::zero(),
+            raw_energy: true,
+            cepstral_lifter: T::from(22).unwrap(),
+            htk_compat: false,
+        }
+    }
+}
+
+/// Floor of the logarithms, the float32 epsilon of `torchaudio.compliance.kaldi` for every `T`.
+#[inline(always)]
+fn epsilon<T: StftNum>() -> T {
+    T::from(f32::EPSILON).unwrap()
+}
+
+#[inline(always)]
+fn mel_scale<T: StftNum>(f: T) -> T {
+    T::from(1127).unwrap() * (T::one() + f / T::from(700).unwrap()).ln()
+}
+
+/// Mel filter bank of shape `[num_bins, padded_window_size / 2 + 1]` using the mel scale
+/// `1127 ln(1 + f / 700)`. The filters are triangles on the mel scale and the Nyquist bin is
+/// always zero, like `torchaudio.compliance.kaldi.get_mel_banks`.
+pub fn mel_banks<T: StftNum>(
+    opts: &MelBanksOptions<T>,
+    sample_frequency: T,
+    padded_window_size: usize,
+) -> Result<Array2<T>> {
+    if opts.num_bins <= 3 {
+        return Err(From::from("Kaldi mel banks require more than 3 bins"));
+    }
+    if padded_window_size % 2 != 0 {
+        return Err(From::from("Kaldi padded frame length must be even"));
+    }
+    let nyquist = sample_frequency / T::from(2).unwrap();
+    let high_freq = if opts.high_freq <= T::zero() {
+        opts.high_freq + nyquist
+    } else {
+        opts.high_freq
+    };
+    if opts.low_freq < T::zero()
+        || opts.low_freq >= nyquist
+        || high_freq <= T::zero()
+        || high_freq > nyquist
+        || opts.low_freq >= high_freq
+    {
+        return Err(From::from(
+            "Kaldi mel banks require 0 <= low_freq < high_freq <= Nyquist",
+        ));
+    }
+    let fft_bin_width = sample_frequency / T::from(padded_window_size).unwrap();
+    let mel_low = mel_scale(opts.low_freq);
+    let mel_delta = (mel_scale(high_freq) - mel_low) / T::from(opts.num_bins + 1).unwrap();
+
+    let mut banks = Array2::zeros((opts.num_bins, padded_window_size / 2 + 1));
+    for (m, mut bank) in banks.outer_iter_mut().enumerate() {
+        let left = mel_low + T::from(m).unwrap() * mel_delta;
+        let center = left + mel_delta;
+        let right = center + mel_delta;
+        for (i, b) in bank.iter_mut().take(padded_window_size / 2).enumerate() {
+            let mel = mel_scale(fft_bin_width * T::from(i).unwrap());
+            let up = (mel - left) / (center - left);
+            let down = (right - mel) / (right - center);
+            *b = up.min(down).max(T::zero());
+        }
+    }
+    Ok(banks)
+}
+
+/// Frames of the waveform after dithering, DC removal, pre-emphasis and windowing, zero-padded
+/// to `padded_window_size`, and their log energies.
+fn extract_frames<T: StftNum>(
+    waveform: ArrayView1<T>,
+    opts: &FrameExtractionOptions<T>,
+    energy_floor: T,
+    raw_energy: bool,
+) -> Result<(Array2<T>, Array1<T>)> {
+    opts.validate()?;
+    let num_samples = waveform.len();
+    let (size, shift) = (opts.window_size(), opts.window_shift());
+    if opts.snip_edges && num_samples < size {
+        return Err(From::from("Signal is shorter than the Kaldi frame length"));
+    }
+    let num_frames = opts.num_frames(num_samples);
+    let window = opts.window();
+    let padded = opts.padded_window_size();
+    let log_energy_floor = if energy_floor > T::zero() {
+        energy_floor.ln()
+    } else {
+        T::neg_infinity()
+    };
+    let log_energy = |frame: ArrayView1<T>| {
+        frame
+            .fold(T::zero(), |a, &v| a + v * v)
+            .max(epsilon())
+            .ln()
+            .max(log_energy_floor)
+    };
+    let normal = Normal::new(0., 1.);
+    let mut rng = rand::thread_rng();
+
+    let mut frames = Array2::zeros((num_frames, padded));
+    let mut energies = Array1::zeros(num_frames);
+    // Without snip_edges, frames are centered on multiples of the shift
+    let offset = if opts.snip_edges {
+        0
+    } else {
+        (shift / 2) as isize - (size / 2) as isize
+    };
+    let n = num_samples as isize;
+    for (i, (mut frame, energy)) in frames.outer_iter_mut().zip(energies.iter_mut()).enumerate() {
+        let start = (i * shift) as isize + offset;
+        for (j, v) in frame.slice_mut(s![..size]).iter_mut().enumerate() {
+            // Symmetric reflection at the edges, repeatedly for signals shorter than a frame
+            let mut k = start + j as isize;
+            while k < 0 || k >= n {
+                k = if k < 0 { -k - 1 } else { 2 * n - 1 - k };
+            }
+            *v = waveform[k as usize];
+        }
+        let mut samples = frame.slice_mut(s![..size]);
+        if opts.dither > T::zero() {
+            samples.mapv_inplace(|v| v + opts.dither * T::from(normal.sample(&mut rng)).unwrap());
+        }
+        if opts.remove_dc_offset {
+            let mean = samples.scalar_sum() / T::from(size).unwrap();
+            samples.mapv_inplace(|v| v - mean);
+        }
+        if raw_energy {
+            *energy = log_energy(samples.view());
+        }
+        if opts.preemph_coeff > T::zero() {
+            for j in (1..size).rev() {
+                samples[j] = samples[j] - opts.preemph_coeff * samples[j - 1];
+            }
+            samples[0] = samples[0] - opts.preemph_coeff * samples[0];
+        }
+        samples.zip_mut_with(&window, |v, &w| *v = *v * w);
+        if !raw_energy {
+            *energy = log_energy(frame.view());
+        }
+    }
+    Ok((frames, energies))
+}
+
+/// Power spectra of shape `[padded_window_size / 2 + 1, num_frames]` of the extracted frames.
+fn power_spectra<T: StftNum>(frames: &Array2<T>) -> Array2<T> {
+    let (num_frames, padded) = frames.dim();
+    let fft = RealFft::new(padded);
+    let mut buf_in = vec![Complex::new(T::zero(), T::zero()); fft.scratch_len()];
+    let mut buf_out = buf_in.clone();
+    let mut spectrum = vec![Complex::new(T::zero(), T::zero()); padded / 2 + 1];
+    let mut samples = vec![T::zero(); padded];
+    let mut power = Array2::zeros((padded / 2 + 1, num_frames).f());
+    for (frame, mut out) in frames.outer_iter().zip(power.axis_iter_mut(Axis(1))) {
+        for (s, &v) in samples.iter_mut().zip(frame.iter()) {
+            *s = v;
+        }
+        fft.process(&samples, &mut buf_in, &mut buf_out, &mut spectrum);
+        for (o, v) in out.iter_mut().zip(spectrum.iter()) {
+            *o = v.norm_sqr();
+        }
+    }
+    power
+}
+
+/// Log power spectrogram of shape `[padded_window_size / 2 + 1, num_frames]` like Kaldi's
+/// `compute-spectrogram-feats`. The first bin is replaced by the log energy of the frame.
+pub fn spectrogram<'a, T, S>(waveform: S, opts: &SpectrogramOptions<T>) -> Result<Array2<T>>
+where
+    T: StftNum,
+    S: AsArray<'a, T>,
+{
+    let (frames, log_energy) = extract_frames(
+        waveform.into(),
+        &opts.frame_opts,
+        opts.energy_floor,
+        opts.raw_energy,
+    )?;
+    let mut spec = power_spectra(&frames);
+    spec.mapv_inplace(|v| v.max(epsilon()).ln());
+    spec.row_mut(0).assign(&log_energy);
+    Ok(spec)
+}
+
+/// Mel filter bank features of shape `[num_bins, num_frames]` like Kaldi's
+/// `compute-fbank-feats`. With `use_energy` the log energy is added as the first feature, or
+/// the last with `htk_compat`.
+pub fn fbank<'a, T, S>(waveform: S, opts: &FbankOptions<T>) -> Result<Array2<T>>
+where
+    T: StftNum,
+    S: AsArray<'a, T>,
+{
+    let (frames, log_energy) = extract_frames(
+        waveform.into(),
+        &opts.frame_opts,
+        opts.energy_floor,
+        opts.raw_energy,
+    )?;
+    let mut spec = power_spectra(&frames);
+    if !opts.use_power {
+        spec.mapv_inplace(T::sqrt);
+    }
+    let banks = mel_banks(
+        &opts.mel_opts,
+        opts.frame_opts.sample_frequency,
+        opts.frame_opts.padded_window_size(),
+    )?;
+    let mut mel = banks.dot(&spec);
+    if opts.use_log_fbank {
+        mel.mapv_inplace(|v| v.max(epsilon()).ln());
+    }
+    if !opts.use_energy {
+        return Ok(mel);
+    }
+    let num_bins = opts.mel_opts.num_bins;
+    let mut features = Array2::zeros((num_bins + 1, frames.shape()[0]).f());
+    let (energy_row, mel_rows) = if opts.htk_compat {
+        (num_bins, s![..num_bins, ..])
+    } else {
+        (0, s![1.., ..])
+    };
+    features.slice_mut(mel_rows).assign(&mel);
+    features.row_mut(energy_row).assign(&log_energy);
+    Ok(features)
+}
+
+/// Mel-frequency cepstral coefficients of shape `[num_ceps, num_frames]` like Kaldi's
+/// `compute-mfcc-feats`.
+///
+/// The log mel energies of `fbank()` are transformed by an orthonormal DCT-II and liftered by
+/// `1 + cepstral_lifter / 2 * sin(pi * k / cepstral_lifter)`. With `htk_compat`, `c0` (or the
+/// energy) is moved to the end.
+pub fn mfcc<'a, T, S>(waveform: S, opts: &MfccOptions<T>) -> Result<Array2<T>>
+where
+    T: StftNum,
+    S: AsArray<'a, T>,
+{
+    if opts.num_ceps == 0 || opts.num_ceps > opts.mel_opts.num_bins {
+        return Err(From::from("Kaldi num_ceps must be in [1, num_bins]"));
+    }
+    let (frames, log_energy) = extract_frames(
+        waveform.into(),
+        &opts.frame_opts,
+        opts.energy_floor,
+        opts.raw_energy,
+    )?;
+    let banks = mel_banks(
+        &opts.mel_opts,
+        opts.frame_opts.sample_frequency,
+        opts.frame_opts.padded_window_size(),
+    )?;
+    let mut mel = banks.dot(&power_spectra(&frames));
+    mel.mapv_inplace(|v| v.max(epsilon()).ln());
+
+    let mut ceps = filters::dct(opts.num_ceps, opts.mel_opts.num_bins).dot(&mel);
+    if opts.cepstral_lifter != T::zero() {
+        let half = opts.cepstral_lifter / T::from(2).unwrap();
+        let pi = T::from(PI).unwrap();
+        for (k, mut row) in ceps.outer_iter_mut().enumerate() {
+            let weight = T::one() + half * (pi * T::from(k).unwrap() / opts.cepstral_lifter).sin();
+            row.mapv_inplace(|v| v * weight);
+        }
+    }
+    if opts.use_energy {
+        ceps.row_mut(0).assign(&log_energy);
+    }
+
+    let mut features = Array2::zeros(ceps.dim().f());
+    if opts.htk_compat {
+        let num_ceps = opts.num_ceps;
+        features
+            .slice_mut(s![..num_ceps - 1, ..])
+            .assign(&ceps.slice(s![1.., ..]));
+        let scale = if opts.use_energy {
+            T::one()
+        } else {
+            T::from(2).unwrap().sqrt()
+        };
+        features
+            .row_mut(num_ceps - 1)
+            .assign(&ceps.row(0).mapv(|v| v * scale));
+    } else {
+        features.assign(&ceps);
+    }
+    Ok(features)
+}
@@ -1,13 +1,29 @@
 #[macro_use]
 extern crate ndarray;
 extern crate num_traits;
+extern crate rand;
+#[cfg(feature = "rayon")]
+extern crate rayon;
 extern crate rustfft;
 
 use ndarray::ScalarOperand;
 use num_traits::Float;
 
+mod cqt;
+pub mod features;
 pub mod filters;
+mod griffinlim;
+mod inverse;
+pub mod kaldi;
+mod mfcc;
+mod multitaper;
+mod nsgt;
+mod psd;
+mod realfft;
+mod reassigned;
 mod spectrum;
+mod streaming;
+pub mod whisper;
 pub mod windows;
 
 pub trait StftNum: Float + rustfft::FFTnum + ScalarOperand {}
@@ -15,5 +31,21 @@ pub trait StftNum: Float + rustfft::FFTnum + ScalarOperand {}
 impl StftNum for f32 {}
 impl StftNum for f64 {}
 
-pub use crate::spectrum::{amplitude_to_db, normalize, power_to_db, PadMode, Stft, StftBuilder};
+pub use crate::cqt::{Cqt, CqtBuilder};
+pub use crate::griffinlim::{GriffinLim, GriffinLimBuilder, PhaseInit};
+pub use crate::inverse::{mel_to_audio, mel_to_stft};
+pub use crate::mfcc::{Mfcc, MfccBuilder};
+pub use crate::multitaper::{Multitaper, MultitaperBuilder};
+pub use crate::nsgt::{FrequencyScale, Nsgt, NsgtBuilder};
+pub use crate::psd::{periodogram, welch, Average, Detrend, Scaling, Welch, WelchBuilder};
+pub use crate::reassigned::{
+    reassigned_spectrogram, ReassignedSpectrogram, Reassignment, ReassignmentBuilder,
+};
+#[cfg(feature = "rayon")]
+pub use crate::spectrum::Parallelism;
+pub use crate::spectrum::{
+    amplitude_to_db, normalize, power_to_db, OutputMode, PadMode, Stft, StftBuilder, StftWorkspace,
+};
+pub use crate::streaming::StreamingStft;
 pub use crate::windows::{get_window, hamming, hann, Window};
+pub use rustfft::num_complex::Complex;
@@ -0,0 +1,209 @@
+use ndarray::prelude::*;
+use ndarray::AsArray;
+use num_traits::FromPrimitive;
+
+use crate::filters::{self, MelNorm, MelScale};
+use crate::spectrum::{Stft, StftBuilder};
+use crate::StftNum;
+
+type Result<T> = ::std::result::Result<T, Box<::std::error::Error>>;
+
+/// Mel-frequency cepstral coefficients.
+///
+/// The power spectrogram of the STFT is projected onto the mel filter bank of
+/// `filters::mel_with()`, converted to dB and decorrelated with the orthonormal type-II DCT of
+/// `filters::dct()`. The defaults use the HTK mel scale without filter normalization on the
+/// normalized STFT of `StftBuilder`, which differs from librosa. With the Slaney mel scale and
+/// normalization on an unnormalized STFT with `PadMode::Constant(0.)` this equals
+/// `librosa.feature.mfcc(y=...)`.
+pub struct Mfcc<T> {
+    pub sr: usize,
+    pub n_mfcc: usize,
+    /// Cepstral liftering coefficient, 0 disables liftering.
+    pub lifter: T,
+    /// Dynamic range of the log-mel spectrogram in dB below its maximum.
+    pub top_db: T,
+    /// Replace the first coefficient by the log energy of the frame.
+    pub energy: bool,
+    /// Mel filter bank of shape `[n_fft / 2 + 1, n_mels]`.
+    pub mel_basis: Array2<T>,
+    /// DCT matrix of shape `[n_mfcc, n_mels]`.
+    pub dct: Array2<T>,
+    stft: Stft<T>,
+}
+
+#[derive(Default)]
+pub struct MfccBuilder<T> {
+    stft: Option<Stft<T>>,
+    sr: Option<usize>,
+    n_mfcc: Option<usize>,
+    n_mels: Option<usize>,
+    f_min: Option<T>,
+    f_max: Option<T>,
+    mel_scale: Option<MelScale>,
+    mel_norm: Option<Option<MelNorm>>,
+    lifter: Option<T>,
+    top_db: Option<T>,
+    energy: Option<bool>,
+}
+
+impl<T: StftNum + FromPrimitive + std::fmt::Debug> MfccBuilder<T> {
+    pub fn new() -> MfccBuilder<T> {
+        MfccBuilder {
+            stft: None,
+            sr: None,
+            n_mfcc: None,
+            n_mels: None,
+            f_min: None,
+            f_max: None,
+            mel_scale: None,
+            mel_norm: None,
+            lifter: None,
+            top_db: None,
+            energy: None,
+        }
+    }
+    /// STFT computing the spectrogram, `StftBuilder::new().build()` by default. Its output mode
+    /// is ignored, the power spectrogram is always used.
+    pub fn stft(mut self, stft: Stft<T>) -> MfccBuilder<T> {
+        self.stft = Some(stft);
+        self
+    }
+    pub fn sr(mut self, sr: usize) -> MfccBuilder<T> {
+        self.sr = Some(sr);
+        self
+    }
+    pub fn n_mfcc(mut self, n_mfcc: usize) -> MfccBuilder<T> {
+        self.n_mfcc = Some(n_mfcc);
+        self
+    }
+    pub fn n_mels(mut self, n_mels: usize) -> MfccBuilder<T> {
+        self.n_mels = Some(n_mels);
+        self
+    }
+    pub fn f_min(mut self, f_min: T) -> MfccBuilder<T> {
+        self.f_min = Some(f_min);
+        self
+    }
+    pub fn f_max(mut self, f_max: T) -> MfccBuilder<T> {
+        self.f_max = Some(f_max);
+        self
+    }
+    /// Mel scale of the filter bank, `MelScale::Htk` by default.
+    pub fn mel_scale(mut self, mel_scale: MelScale) -> MfccBuilder<T> {
+        self.mel_scale = Some(mel_scale);
+        self
+    }
+    /// Normalization of the mel filters, `None` by default.
+    pub fn mel_norm(mut self, mel_norm: Option<MelNorm>) -> MfccBuilder<T> {
+        self.mel_norm = Some(mel_norm);
+        self
+    }
+    /// Sinusoidal liftering of coefficient `k` by `1 + lifter / 2 * sin(pi * (k + 1) / lifter)`.
+    pub fn lifter(mut self, lifter: T) -> MfccBuilder<T> {
+        self.lifter = Some(lifter);
+        self
+    }
+    /// Clips the log-mel spectrogram to `max - top_db`, 80 dB by default. An infinite value
+    /// disables the clipping.
+    pub fn top_db(mut self, top_db: T) -> MfccBuilder<T> {
+        self.top_db = Some(top_db);
+        self
+    }
+    /// Replace `c0` by the natural logarithm of the energy `sum(x[n]^2)` of the windowed frame.
+    ///
+    /// The window of a normalized STFT is normalized as well.
+    pub fn energy(mut self, energy: bool) -> MfccBuilder<T> {
+        self.energy = Some(energy);
+        self
+    }
+    pub fn build(self) -> Result<Mfcc<T>> {
+        let stft = match self.stft {
+            Some(stft) => stft,
+            None => StftBuilder::new().build()?,
+        };
+        let sr = self.sr.unwrap_or(22050);
+        let n_mfcc = self.n_mfcc.unwrap_or(20);
+        let n_mels = self.n_mels.unwrap_or(128);
+        if n_mfcc == 0 || n_mfcc > n_mels {
+            return Err(From::from("MFCC n_mfcc must be in [1, n_mels]"));
+        }
+        let lifter = self.lifter.unwrap_or_else(T::zero);
+        if lifter < T::zero() {
+            return Err(From::from("MFCC lifter must be >= 0"));
+        }
+        let top_db = self.top_db.unwrap_or_else(|| T::from(80).unwrap());
+        if top_db < T::zero() {
+            return Err(From::from("MFCC top_db must be >= 0"));
+        }
+        Ok(Mfcc {
+            sr,
+            n_mfcc,
+            lifter,
+            top_db,
+            energy: self.energy.unwrap_or(false),
+            mel_basis: filters::mel_with(
+                sr,
+                stft.n_fft,
+                n_mels,
+                self.f_min,
+                self.f_max,
+                self.mel_scale.unwrap_or(MelScale::Htk),
+                self.mel_norm.unwrap_or(None),
+            )?,
+            dct: filters::dct(n_mfcc, n_mels),
+            stft,
+        })
+    }
+}
+
+impl<T: StftNum + std::fmt::Debug + std::fmt::Display> Mfcc<T> {
+    /// The STFT providing the spectrogram.
+    pub fn stft(&self) -> &Stft<T> {
+        &self.stft
+    }
+
+    /// Computes the MFCCs of shape `[n_mfcc, n_frames]`.
+    pub fn process<'a, S>(&self, signal: S) -> Result<Array2<T>>
+    where
+        S: AsArray<'a, T>,
+        T: 'a,
+    {
+        let spec = self.stft.process_complex_view(signal)?;
+        let power = spec.map(|v| v.norm_sqr());
+        let amin = T::from(1e-10).unwrap();
+
+        // Log-mel spectrogram like librosa.power_to_db() with a reference of 1
+        let mut log_mel = self.mel_basis.t().dot(&power);
+        let ten = T::from(10).unwrap();
+        log_mel.mapv_inplace(|v| ten * v.max(amin).log10());
+        let floor = log_mel.fold(T::neg_infinity(), |a, &b| a.max(b)) - self.top_db;
+        log_mel.mapv_inplace(|v| v.max(floor));
+
+        let mut mfcc = Array2::zeros((self.n_mfcc, power.shape()[1]).f());
+        mfcc.assign(&self.dct.dot(&log_mel));
+        if self.lifter > T::zero() {
+            let half = self.lifter / T::from(2).unwrap();
+            let pi = T::from(std::f64::consts::PI).unwrap();
+            for (k, mut row) in mfcc.outer_iter_mut().enumerate() {
+                let weight = T::one() + half * (pi * T::from(k + 1).unwrap() / self.lifter).sin();
+                row.mapv_inplace(|v| v * weight);
+            }
+        }
+        if self.energy {
+            // Energy of the frame by Parseval's theorem. Except for DC and the Nyquist bin of
+            // an even n_fft, the bins of the one-sided spectrum stand for two bins.
+            let n_fft = self.stft.n_fft;
+            let n_freqs = power.shape()[0];
+            let two = T::from(2).unwrap();
+            for (c, frame) in mfcc.row_mut(0).iter_mut().zip(power.axis_iter(Axis(1))) {
+                let mut energy = two * frame.scalar_sum() - frame[0];
+                if n_fft % 2 == 0 {
+                    energy = energy - frame[n_freqs - 1];
+                }
+                *c = (energy / T::from(n_fft).unwrap()).max(amin).ln();
+            }
+        }
+        Ok(mfcc)
+    }
+}
@@ -0,0 +1,217 @@
+use ndarray::prelude::*;
+use ndarray::{AsArray, Zip};
+
+use crate::spectrum::{PadMode, Stft, StftBuilder};
+use crate::windows;
+use crate::StftNum;
+
+type Result<T> = ::std::result::Result<T, Box<::std::error::Error>>;
+
+/// Multitaper power spectrogram using DPSS (Slepian) tapers.
+///
+/// Every frame is analyzed with `k` orthogonal tapers of the half bandwidth `nw`. The tapers
+/// are periodic like the windows of the STFT, equal to
+/// `scipy.signal.windows.dpss(n_fft, nw, k, sym=False)`. The power spectra of the tapers are
+/// averaged, which reduces the variance compared to a single window. With `adaptive` the
+/// tapers are weighted per bin using Thomson's adaptive scheme, which reduces the broadband
+/// leakage of the higher order tapers.
+pub struct Multitaper<T> {
+    pub nw: T,
+    pub adaptive: bool,
+    /// Maximum number of iterations of the adaptive weighting.
+    pub max_iter: usize,
+    /// Periodic DPSS tapers of shape `[k, n_fft]`.
+    pub tapers: Array2<T>,
+    /// Concentration ratios of the tapers.
+    pub ratios: Array1<T>,
+    stft: Stft<T>,
+}
+
+#[derive(Default)]
+pub struct MultitaperBuilder<T> {
+    n_fft: Option<usize>,
+    hop_length: Option<usize>,
+    pad_mode: Option<PadMode<T>>,
+    nw: Option<T>,
+    k: Option<usize>,
+    adaptive: Option<bool>,
+    max_iter: Option<usize>,
+}
+
+impl<T: StftNum> MultitaperBuilder<T> {
+    pub fn new() -> MultitaperBuilder<T> {
+        MultitaperBuilder {
+            n_fft: None,
+            hop_length: None,
+            pad_mode: None,
+            nw: None,
+            k: None,
+            adaptive: None,
+            max_iter: None,
+        }
+    }
+    pub fn n_fft(mut self, n_fft: usize) -> MultitaperBuilder<T> {
+        self.n_fft = Some(n_fft);
+        self
+    }
+    pub fn hop_length(mut self, hop_length: usize) -> MultitaperBuilder<T> {
+        self.hop_length = Some(hop_length);
+        self
+    }
+    pub fn pad_mode(mut self, pad_mode: PadMode<T>) -> MultitaperBuilder<T> {
+        self.pad_mode = Some(pad_mode);
+        self
+    }
+    /// Time-half-bandwidth product of the tapers, 4 by default.
+    pub fn nw(mut self, nw: T) -> MultitaperBuilder<T> {
+        self.nw = Some(nw);
+        self
+    }
+    /// Number of tapers, `2 nw - 1` by default.
+    pub fn k(mut self, k: usize) -> MultitaperBuilder<T> {
+        self.k = Some(k);
+        self
+    }
+    pub fn adaptive(mut self, adaptive: bool) -> MultitaperBuilder<T> {
+        self.adaptive = Some(adaptive);
+        self
+    }
+    pub fn max_iter(mut self, max_iter: usize) -> MultitaperBuilder<T> {
+        self.max_iter = Some(max_iter);
+        self
+    }
+    pub fn build(self) -> Result<Multitaper<T>> {
+        let n_fft = self.n_fft.unwrap_or(2048);
+        let nw = self.nw.unwrap_or_else(|| T::from(4).unwrap());
+        if nw <= T::zero() || nw >= T::from(n_fft as f64 / 2.).unwrap() {
+            return Err(From::from("Multitaper nw must be in (0, n_fft / 2)"));
+        }
+        let k = self.k.unwrap_or_else(|| {
+            let k = (T::from(2).unwrap() * nw).floor().to_usize().unwrap();
+            if k > 1 {
+                k - 1
+            } else {
+                1
+            }
+        });
+        if k == 0 || k > n_fft {
+            return Err(From::from("Multitaper k must be in [1, n_fft]"));
+        }
+        let (tapers, ratios) = windows::dpss(n_fft, nw, k, false);
+        let builder = StftBuilder::new()
+            .n_fft(n_fft)
+            .pad_mode(self.pad_mode.unwrap_or_default())
+            .window(tapers.row(0).to_owned())
+            .normalize(false);
+        let builder = match self.hop_length {
+            Some(hop_length) => builder.hop_length(hop_length),
+            None => builder,
+        };
+        Ok(Multitaper {
+            nw,
+            adaptive: self.adaptive.unwrap_or(false),
+            max_iter: self.max_iter.unwrap_or(100),
+            tapers,
+            ratios,
+            stft: builder.build()?,
+        })
+    }
+}
+
+impl<T: StftNum + std::fmt::Debug + std::fmt::Display> Multitaper<T> {
+    /// The STFT providing the framing and padding.
+    pub fn stft(&self) -> &Stft<T> {
+        &self.stft
+    }
+
+    /// Computes the multitaper power spectrogram of shape `[n_freqs, n_frames]`.
+    ///
+    /// The periodic tapers are truncated from tapers of unit energy, so the power of white noise
+    /// with variance `s` is close to `s` in every bin.
+    pub fn process<'a, S>(&self, signal: S) -> Result<Array2<T>>
+    where
+        S: AsArray<'a, T>,
+        T: 'a,
+    {
+        let signal = signal.into();
+        let (n_freqs, n_frames) = self.stft.output_shape(signal.len())?;
+        let k = self.tapers.shape()[0];
+        // Power spectrogram of every taper
+        let mut powers = Array3::<T>::zeros((k, n_freqs, n_frames));
+        for (taper, mut power) in self.tapers.outer_iter().zip(powers.outer_iter_mut()) {
+            let spec = self.stft.process_complex_windowed(signal, taper)?;
+            Zip::from(&mut power)
+                .and(&spec)
+                .apply(|p, v| *p = v.norm_sqr());
+        }
+
+        let mut output = Array2::<T>::zeros((n_freqs, n_frames).f());
+        output.assign(&(powers.sum_axis(Axis(0)) / T::from(k).unwrap()));
+        if self.adaptive {
+            for (t, mut frame) in output.axis_iter_mut(Axis(1)).enumerate() {
+                let frame_powers = powers.slice(s![.., .., t]);
+                self.adaptive_weighting(frame_powers, frame.view_mut());
+            }
+        }
+        Ok(output)
+    }
+
+    /// Replaces the averaged `estimate` of one frame by Thomson's adaptively weighted
+    /// estimate.
+    ///
+    /// The weight of taper `k` is `d_k = sqrt(l_k) S / (l_k S + (1 - l_k) s)`, where `l_k` is
+    /// the concentration ratio, `S` the current estimate and `s` the variance of the frame. The
+    /// estimate `sum(d_k^2 S_k) / sum(d_k^2)` is iterated until the weights converge.
+    fn adaptive_weighting(&self, powers: ArrayView2<T>, mut estimate: ArrayViewMut1<T>) {
+        let n_freqs = estimate.len();
+        let n_fft = self.stft.n_fft;
+        let two = T::from(2).unwrap();
+        // Variance of the frame by Parseval's theorem on the one-sided spectrum
+        let mut variance = estimate[0];
+        for f in 1..n_freqs {
+            let double = f < n_freqs - 1 || n_fft % 2 == 1;
+            variance = variance
+                + if double {
+                    two * estimate[f]
+                } else {
+                    estimate[f]
+                };
+        }
+        let variance = variance / T::from(n_fft).unwrap();
+
+        let tol = T::from(1e-10).unwrap();
+        let k = self.ratios.len();
+        let mut weights = vec![T::zero(); k];
+        for (f, s) in estimate.iter_mut().enumerate() {
+            let s_k = powers.column(f);
+            weights.iter_mut().for_each(|w| *w = T::zero());
+            // Start with the first two tapers
+            let n_init = k.min(2);
+            let mut s_est = (0..n_init)
+                .map(|i| self.ratios[i] * s_k[i])
+                .fold(T::zero(), |a, b| a + b)
+                / self.ratios.slice(s![..n_init]).scalar_sum();
+            for _ in 0..self.max_iter {
+                let mut change = T::zero();
+                let mut num = T::zero();
+                let mut den = T::zero();
+                for i in 0..k {
+                    let l = self.ratios[i];
+                    let d = l.sqrt() * s_est / (l * s_est + (T::one() - l) * variance);
+                    let d = if d.is_finite() { d } else { T::zero() };
+                    change = change + (d - weights[i]).powi(2);
+                    weights[i] = d;
+                    num = num + d * d * s_k[i];
+                    den = den + d * d;
+                }
+                if den > T::zero() {
+                    s_est = num / den;
+                }
+                if change / T::from(k).unwrap() < tol {
+                    break;
+                }
+            }
+            *s = s_est;
+        }
+    }
+}
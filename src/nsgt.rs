@@ -0,0 +1,384 @@
+use std::sync::Arc;
+
+use ndarray::prelude::*;
+use ndarray::AsArray;
+use num_traits::Zero;
+use rustfft::num_complex::Complex;
+use rustfft::{FFTplanner, FFT};
+
+use crate::filters::MelScale;
+use crate::realfft::RealFft;
+use crate::windows::{self, Window};
+use crate::StftNum;
+
+type Result<T> = ::std::result::Result<T, Box<::std::error::Error>>;
+
+/// Frequency scale on which the bands of a `Nsgt` are equally spaced.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FrequencyScale {
+    /// Logarithmic scale with a constant ratio between neighboring bands.
+    Log,
+    /// Mel scale `2595 log10(1 + f / 700)`.
+    Mel,
+    /// Bark scale `6 asinh(f / 600)`.
+    Bark,
+}
+
+impl Default for FrequencyScale {
+    fn default() -> FrequencyScale {
+        FrequencyScale::Log
+    }
+}
+
+impl FrequencyScale {
+    /// Center frequencies of `n_bins` bands from `fmin` to `fmax`.
+    fn frequencies<T: StftNum>(self, fmin: T, fmax: T, n_bins: usize) -> Vec<T> {
+        let six = T::from(6).unwrap();
+        let bark_hz = T::from(600).unwrap();
+        let (lo, hi) = match self {
+            FrequencyScale::Log => (fmin.ln(), fmax.ln()),
+            FrequencyScale::Mel => (MelScale::Htk.hz_to_mel(fmin), MelScale::Htk.hz_to_mel(fmax)),
+            FrequencyScale::Bark => (
+                (fmin / bark_hz).asinh() * six,
+                (fmax / bark_hz).asinh() * six,
+            ),
+        };
+        let step = if n_bins > 1 {
+            (hi - lo) / T::from(n_bins - 1).unwrap()
+        } else {
+            T::zero()
+        };
+        (0..n_bins)
+            .map(|k| {
+                let z = lo + step * T::from(k).unwrap();
+                match self {
+                    FrequencyScale::Log => z.exp(),
+                    FrequencyScale::Mel => MelScale::Htk.mel_to_hz(z),
+                    FrequencyScale::Bark => (z / six).sinh() * bark_hz,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Invertible nonstationary Gabor transform (NSGT) of signals with a fixed length.
+///
+/// The spectrum of the whole signal is split into bands by windows that are centered at
+/// frequencies equally spaced on a `FrequencyScale`, plus a DC and a Nyquist band. Every band
+/// is shifted to baseband and transformed back to the time domain with as many coefficients as
+/// its window has frequency bins. Low bands thus have few coefficients and a fine frequency
+/// resolution, high bands many coefficients and a fine time resolution.
+///
+/// As every window is shorter than its number of coefficients, the frame operator is diagonal
+/// and the canonical dual windows are `g_k / sum_j g_j^2`, which makes `inverse()` exact
+/// ("painless" case). See Velasco et al., "Constructing an invertible constant-Q transform with
+/// nonstationary Gabor frames", DAFx 2011.
+pub struct Nsgt<T> {
+    pub sr: T,
+    /// Number of samples of the signal.
+    pub length: usize,
+    /// Center frequencies of the bands in Hz, from the DC to the Nyquist band.
+    pub frequencies: Array1<T>,
+    bands: Vec<Band<T>>,
+    fft: RealFft<T>,
+    ifft: Arc<FFT<T>>,
+}
+
+/// Frequency window of one band.
+struct Band<T> {
+    // FFT bin of the center of the window
+    center: usize,
+    window: Array1<T>,
+    dual: Array1<T>,
+    fft: Arc<FFT<T>>,
+    ifft: Arc<FFT<T>>,
+}
+
+#[derive(Default)]
+pub struct NsgtBuilder<T> {
+    sr: Option<T>,
+    length: Option<usize>,
+    scale: Option<FrequencyScale>,
+    fmin: Option<T>,
+    fmax: Option<T>,
+    n_bins: Option<usize>,
+    window: Option<Window>,
+    min_window: Option<usize>,
+    matrix_form: Option<bool>,
+}
+
+impl<T: StftNum> NsgtBuilder<T> {
+    pub fn new() -> NsgtBuilder<T> {
+        NsgtBuilder {
+            sr: None,
+            length: None,
+            scale: None,
+            fmin: None,
+            fmax: None,
+            n_bins: None,
+            window: None,
+            min_window: None,
+            matrix_form: None,
+        }
+    }
+    /// Sampling rate of the signal, 22050 by default.
+    pub fn sr(mut self, sr: T) -> NsgtBuilder<T> {
+        self.sr = Some(sr);
+        self
+    }
+    /// Number of samples of the signals, required.
+    pub fn length(mut self, length: usize) -> NsgtBuilder<T> {
+        self.length = Some(length);
+        self
+    }
+    pub fn scale(mut self, scale: FrequencyScale) -> NsgtBuilder<T> {
+        self.scale = Some(scale);
+        self
+    }
+    /// Center frequency of the lowest band, C1 (32.70 Hz) by default.
+    pub fn fmin(mut self, fmin: T) -> NsgtBuilder<T> {
+        self.fmin = Some(fmin);
+        self
+    }
+    /// Center frequency of the highest band, B7 (3951 Hz) by default.
+    pub fn fmax(mut self, fmax: T) -> NsgtBuilder<T> {
+        self.fmax = Some(fmax);
+        self
+    }
+    /// Number of bands between `fmin` and `fmax`, 84 by default.
+    pub fn n_bins(mut self, n_bins: usize) -> NsgtBuilder<T> {
+        self.n_bins = Some(n_bins);
+        self
+    }
+    /// Shape of the frequency windows, `Window::Hann` by default.
+    pub fn window(mut self, window: Window) -> NsgtBuilder<T> {
+        self.window = Some(window);
+        self
+    }
+    /// Minimum number of frequency bins of a window, 4 by default.
+    pub fn min_window(mut self, min_window: usize) -> NsgtBuilder<T> {
+        self.min_window = Some(min_window);
+        self
+    }
+    /// Uses the same number of coefficients for all bands, so that they can be stacked into a
+    /// matrix.
+    pub fn matrix_form(mut self, matrix_form: bool) -> NsgtBuilder<T> {
+        self.matrix_form = Some(matrix_form);
+        self
+    }
+    pub fn build(self) -> Result<Nsgt<T>> {
+        let sr = self.sr.unwrap_or_else(|| T::from(22050).unwrap());
+        let length = match self.length {
+            Some(length) if length >= 2 => length,
+            _ => return Err(From::from("NSGT length must be set and >= 2")),
+        };
+        let fmin = self
+            .fmin
+            .unwrap_or_else(|| T::from(32.703_195_662_574_83).unwrap());
+        let fmax = self
+            .fmax
+            .unwrap_or_else(|| T::from(3_951.066_410_048_992).unwrap());
+        let n_bins = self.n_bins.unwrap_or(84);
+        let window = self.window.unwrap_or_default();
+        let min_window = self.min_window.unwrap_or(4).max(1);
+        let nyquist = sr / T::from(2).unwrap();
+        if n_bins == 0 {
+            return Err(From::from("NSGT n_bins must be > 0"));
+        }
+        if !(fmin > T::zero() && fmin <= fmax && fmax < nyquist) || (n_bins > 1 && fmin == fmax) {
+            return Err(From::from("NSGT requires 0 < fmin < fmax < sr / 2"));
+        }
+
+        // Band centers in Hz and in FFT bins, including DC and Nyquist
+        let mut frequencies = vec![T::zero()];
+        frequencies.extend(
+            self.scale
+                .unwrap_or_default()
+                .frequencies(fmin, fmax, n_bins),
+        );
+        frequencies.push(nyquist);
+        let n_freqs = length / 2 + 1;
+        let bin_width = sr / T::from(length).unwrap();
+        let centers: Vec<T> = frequencies.iter().map(|&f| f / bin_width).collect();
+        let last = centers.len() - 1;
+        // Every window reaches from the center of the previous band to the next one
+        let widths: Vec<usize> = (0..=last)
+            .map(|k| {
+                let width = if k == 0 {
+                    T::from(2).unwrap() * centers[1]
+                } else if k == last {
+                    T::from(2).unwrap() * (centers[last] - centers[last - 1])
+                } else {
+                    centers[k + 1] - centers[k - 1]
+                };
+                width.round().to_usize().unwrap().max(min_window)
+            })
+            .collect();
+        let max_width = widths.iter().cloned().max().unwrap();
+
+        let windows: Vec<Array1<T>> = widths
+            .iter()
+            .map(|&w| windows::get_window(window, w, true))
+            .collect();
+        let centers: Vec<usize> = centers
+            .iter()
+            .map(|c| c.round().to_usize().unwrap().min(n_freqs - 1))
+            .collect();
+
+        // Diagonal of the frame operator on the positive frequencies
+        let mut diagonal = Array1::<T>::zeros(n_freqs);
+        for (&center, g) in centers.iter().zip(&windows) {
+            for_each_bin(center, g.len(), n_freqs, |i, m, _| {
+                diagonal[m] = diagonal[m] + g[i] * g[i]
+            });
+        }
+        if diagonal.iter().any(|&d| d <= T::zero()) {
+            return Err(From::from(
+                "NSGT windows do not cover all frequencies, increase n_bins or min_window",
+            ));
+        }
+
+        let mut planner = FFTplanner::new(false);
+        let mut inverse_planner = FFTplanner::new(true);
+        let bands = centers
+            .into_iter()
+            .zip(windows)
+            .map(|(center, window)| {
+                let mut dual = Array1::zeros(window.len());
+                for_each_bin(center, window.len(), n_freqs, |i, m, _| {
+                    dual[i] = window[i] / diagonal[m]
+                });
+                let n_coefs = if self.matrix_form.unwrap_or(false) {
+                    max_width
+                } else {
+                    window.len()
+                };
+                Band {
+                    center,
+                    window,
+                    dual,
+                    fft: planner.plan_fft(n_coefs),
+                    ifft: inverse_planner.plan_fft(n_coefs),
+                }
+            })
+            .collect();
+
+        Ok(Nsgt {
+            sr,
+            length,
+            frequencies: Array1::from_vec(frequencies),
+            bands,
+            fft: RealFft::new(length),
+            ifft: inverse_planner.plan_fft(length),
+        })
+    }
+}
+
+/// Calls `f` with the window index, the FFT bin and the coefficient index of every bin of a
+/// window of `width` bins centered at `center`, that lies within the `n_freqs` positive
+/// frequencies.
+///
+/// Coefficient indices are circular around the center, which is shifted to 0.
+#[inline]
+fn for_each_bin<F>(center: usize, width: usize, n_freqs: usize, mut f: F)
+where
+    F: FnMut(usize, usize, isize),
+{
+    let half = (width / 2) as isize;
+    for i in 0..width {
+        let offset = i as isize - half;
+        let m = center as isize + offset;
+        if m >= 0 && m < n_freqs as isize {
+            f(i, m as usize, offset);
+        }
+    }
+}
+
+impl<T: StftNum> Nsgt<T> {
+    /// Number of bands including the DC and Nyquist bands.
+    pub fn n_bands(&self) -> usize {
+        self.bands.len()
+    }
+
+    /// Number of coefficients of every band.
+    pub fn n_coefficients(&self) -> Vec<usize> {
+        self.bands.iter().map(|b| b.fft.len()).collect()
+    }
+
+    /// Computes the coefficients of every band, from the DC to the Nyquist band.
+    ///
+    /// Coefficient `j` of band `k` is located at the sample `j * length / n_k`, where `n_k` is
+    /// the number of coefficients of the band.
+    pub fn process<'a, S>(&self, signal: S) -> Result<Vec<Array1<Complex<T>>>>
+    where
+        S: AsArray<'a, T>,
+        T: 'a,
+    {
+        let signal = signal.into();
+        if signal.len() != self.length {
+            return Err(From::from(
+                "Signal length must be equal to the NSGT length.",
+            ));
+        }
+        let n_freqs = self.length / 2 + 1;
+        let mut spectrum = vec![Complex::zero(); n_freqs];
+        let mut buf_in = vec![Complex::zero(); self.fft.scratch_len()];
+        let mut buf_out = vec![Complex::zero(); self.fft.scratch_len()];
+        self.fft
+            .process(&signal.to_vec(), &mut buf_in, &mut buf_out, &mut spectrum);
+
+        let coefficients = self
+            .bands
+            .iter()
+            .map(|band| {
+                let n = band.ifft.len();
+                let mut input = vec![Complex::zero(); n];
+                let mut output = vec![Complex::zero(); n];
+                for_each_bin(band.center, band.window.len(), n_freqs, |i, m, offset| {
+                    input[offset.rem_euclid(n as isize) as usize] = spectrum[m] * band.window[i];
+                });
+                band.ifft.process(&mut input, &mut output);
+                let scale = T::from(n).unwrap().recip();
+                Array1::from_iter(output.into_iter().map(|v| v * scale))
+            })
+            .collect();
+        Ok(coefficients)
+    }
+
+    /// Reconstructs the signal from the coefficients returned by `process()`.
+    pub fn inverse(&self, coefficients: &[Array1<Complex<T>>]) -> Result<Vec<T>> {
+        if coefficients.len() != self.bands.len() {
+            return Err(From::from("Number of bands must be equal to n_bands()."));
+        }
+        let n_freqs = self.length / 2 + 1;
+        let mut spectrum = vec![Complex::zero(); self.length];
+        for (band, coefs) in self.bands.iter().zip(coefficients) {
+            let n = band.fft.len();
+            if coefs.len() != n {
+                return Err(From::from(
+                    "Number of coefficients must be equal to n_coefficients().",
+                ));
+            }
+            let mut input = coefs.to_vec();
+            let mut output = vec![Complex::zero(); n];
+            band.fft.process(&mut input, &mut output);
+            for_each_bin(band.center, band.window.len(), n_freqs, |i, m, offset| {
+                spectrum[m] =
+                    spectrum[m] + output[offset.rem_euclid(n as isize) as usize] * band.dual[i];
+            });
+        }
+
+        // Hermitian spectrum of the real signal
+        spectrum[0].im = T::zero();
+        if self.length % 2 == 0 {
+            spectrum[n_freqs - 1].im = T::zero();
+        }
+        for k in n_freqs..self.length {
+            spectrum[k] = spectrum[self.length - k].conj();
+        }
+        let mut output = vec![Complex::zero(); self.length];
+        self.ifft.process(&mut spectrum, &mut output);
+        let scale = T::from(self.length).unwrap().recip();
+        Ok(output.into_iter().map(|v| v.re * scale).collect())
+    }
+}
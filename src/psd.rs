@@ -0,0 +1,389 @@
+use std::cmp::Ordering;
+
+use ndarray::prelude::*;
+use ndarray::{AsArray, Zip};
+
+use crate::spectrum::{PadMode, Stft, StftBuilder};
+use crate::windows;
+use crate::StftNum;
+
+type Result<T> = ::std::result::Result<T, Box<::std::error::Error>>;
+
+/// Trend removed from every segment before windowing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Detrend {
+    None,
+    /// Subtracts the mean
+    Constant,
+    /// Subtracts the least squares fit of a line
+    Linear,
+}
+
+impl Default for Detrend {
+    fn default() -> Detrend {
+        Detrend::Constant
+    }
+}
+
+/// Unit of the power spectral estimate.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scaling {
+    /// Power spectral density in V**2/Hz
+    Density,
+    /// Power spectrum in V**2
+    Spectrum,
+}
+
+impl Default for Scaling {
+    fn default() -> Scaling {
+        Scaling::Density
+    }
+}
+
+/// Combination of the periodograms of the segments.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Average {
+    Mean,
+    /// Bias corrected median, robust against outliers
+    Median,
+}
+
+impl Default for Average {
+    fn default() -> Average {
+        Average::Mean
+    }
+}
+
+/// Power spectral density estimation by Welch's method of averaged periodograms.
+///
+/// The signal is split into segments of `nperseg` samples overlapping by `noverlap` samples.
+/// Samples after the last complete segment are dropped. Every segment is detrended, windowed
+/// and zero padded to `nfft` samples. Follows `scipy.signal.welch`.
+///
+/// The segments are the frames of an unnormalized `Stft` without padding. The trend is
+/// removed from their spectra, which is equal to detrending before windowing.
+pub struct Welch<T> {
+    pub fs: T,
+    pub window: Array1<T>,
+    pub noverlap: usize,
+    pub nfft: usize,
+    pub detrend: Detrend,
+    pub scaling: Scaling,
+    pub average: Average,
+    pub onesided: bool,
+    stft: Stft<T>,
+}
+
+#[derive(Default)]
+pub struct WelchBuilder<T> {
+    fs: Option<T>,
+    window: Option<Array1<T>>,
+    window_named: Option<windows::Window>,
+    nperseg: Option<usize>,
+    noverlap: Option<usize>,
+    nfft: Option<usize>,
+    detrend: Option<Detrend>,
+    scaling: Option<Scaling>,
+    average: Option<Average>,
+    onesided: Option<bool>,
+}
+
+impl<T: StftNum> WelchBuilder<T> {
+    pub fn new() -> WelchBuilder<T> {
+        WelchBuilder {
+            fs: None,
+            window: None,
+            window_named: None,
+            nperseg: None,
+            noverlap: None,
+            nfft: None,
+            detrend: None,
+            scaling: None,
+            average: None,
+            onesided: None,
+        }
+    }
+    /// Sampling frequency, 1 by default.
+    pub fn fs(mut self, fs: T) -> WelchBuilder<T> {
+        self.fs = Some(fs);
+        self
+    }
+    /// Window of length `nperseg`, which sets `nperseg` if not given.
+    pub fn window(mut self, window: Array1<T>) -> WelchBuilder<T> {
+        self.window = Some(window);
+        self
+    }
+    pub fn window_named(mut self, window: windows::Window) -> WelchBuilder<T> {
+        self.window_named = Some(window);
+        self
+    }
+    /// Length of a segment, 256 by default.
+    pub fn nperseg(mut self, nperseg: usize) -> WelchBuilder<T> {
+        self.nperseg = Some(nperseg);
+        self
+    }
+    /// Overlap of the segments, `nperseg / 2` by default.
+    pub fn noverlap(mut self, noverlap: usize) -> WelchBuilder<T> {
+        self.noverlap = Some(noverlap);
+        self
+    }
+    /// Length of the FFT, `nperseg` by default.
+    pub fn nfft(mut self, nfft: usize) -> WelchBuilder<T> {
+        self.nfft = Some(nfft);
+        self
+    }
+    pub fn detrend(mut self, detrend: Detrend) -> WelchBuilder<T> {
+        self.detrend = Some(detrend);
+        self
+    }
+    pub fn scaling(mut self, scaling: Scaling) -> WelchBuilder<T> {
+        self.scaling = Some(scaling);
+        self
+    }
+    pub fn average(mut self, average: Average) -> WelchBuilder<T> {
+        self.average = Some(average);
+        self
+    }
+    /// Returns the one-sided spectrum with doubled power, `true` by default.
+    pub fn onesided(mut self, onesided: bool) -> WelchBuilder<T> {
+        self.onesided = Some(onesided);
+        self
+    }
+    pub fn build(self) -> Result<Welch<T>> {
+        let window = match self.window {
+            Some(w) => {
+                if self.nperseg.is_some_and(|n| n != w.len()) {
+                    return Err(From::from("Window length must be equal to nperseg."));
+                }
+                w
+            }
+            None => windows::get_window(
+                self.window_named.unwrap_or_default(),
+                self.nperseg.unwrap_or(256),
+                true,
+            ),
+        };
+        let nperseg = window.len();
+        if nperseg == 0 {
+            return Err(From::from("Welch nperseg must be > 0"));
+        }
+        let noverlap = self.noverlap.unwrap_or(nperseg / 2);
+        if noverlap >= nperseg {
+            return Err(From::from("Welch noverlap must be < nperseg"));
+        }
+        let nfft = self.nfft.unwrap_or(nperseg);
+        if nfft < nperseg {
+            return Err(From::from("Welch nfft must be >= nperseg"));
+        }
+        // The segment is zero padded at the end, not centered in the frame
+        let mut stft_window = Array1::<T>::zeros(nfft);
+        stft_window.slice_mut(s![..nperseg]).assign(&window);
+        let stft = StftBuilder::new()
+            .n_fft(nfft)
+            .hop_length(nperseg - noverlap)
+            .window(stft_window)
+            .pad_mode(PadMode::Truncate)
+            .normalize(false)
+            .build()?;
+        Ok(Welch {
+            fs: self.fs.unwrap_or_else(T::one),
+            window,
+            noverlap,
+            nfft,
+            detrend: self.detrend.unwrap_or_default(),
+            scaling: self.scaling.unwrap_or_default(),
+            average: self.average.unwrap_or_default(),
+            onesided: self.onesided.unwrap_or(true),
+            stft,
+        })
+    }
+}
+
+impl<T: StftNum + std::fmt::Debug + std::fmt::Display> Welch<T> {
+    pub fn nperseg(&self) -> usize {
+        self.window.len()
+    }
+
+    /// Number of segments of a signal with `len` samples.
+    pub fn n_segments(&self, len: usize) -> usize {
+        if len < self.nperseg() {
+            return 0;
+        }
+        let step = self.nperseg() - self.noverlap;
+        1 + (len - self.nperseg()) / step
+    }
+
+    /// Frequencies in Hz of the estimate, ordered like `numpy.fft.fftfreq` if two-sided.
+    pub fn frequencies(&self) -> Array1<T> {
+        let df = self.fs / T::from(self.nfft).unwrap();
+        if self.onesided {
+            Array1::from_shape_fn(self.nfft / 2 + 1, |k| T::from(k).unwrap() * df)
+        } else {
+            Array1::from_shape_fn(self.nfft, |k| {
+                let k = k as isize;
+                let k = if k < (self.nfft as isize + 1) / 2 {
+                    k
+                } else {
+                    k - self.nfft as isize
+                };
+                T::from(k).unwrap() * df
+            })
+        }
+    }
+
+    /// Estimates the power spectrum of `signal`.
+    pub fn process<'a, S>(&self, signal: S) -> Result<Array1<T>>
+    where
+        S: AsArray<'a, T>,
+        T: 'a,
+    {
+        let signal: ArrayView1<T> = signal.into();
+        let nperseg = self.nperseg();
+        let n_segments = self.n_segments(signal.len());
+        if n_segments == 0 {
+            return Err(From::from("Signal must not be shorter than nperseg."));
+        }
+        let step = nperseg - self.noverlap;
+        let n_freqs = self.nfft / 2 + 1;
+
+        let scale = match self.scaling {
+            Scaling::Density => T::one() / (self.fs * self.window.map(|w| *w * *w).scalar_sum()),
+            Scaling::Spectrum => T::one() / self.window.scalar_sum().powi(2),
+        };
+
+        // Spectra of the window and of the window times the centered sample index, whose
+        // multiples are the spectra of the constant and linear trend of a segment
+        let t_mean = T::from(nperseg - 1).unwrap() / T::from(2).unwrap();
+        let ones = Array1::<T>::ones(self.nfft);
+        let ramp = Array1::from_shape_fn(self.nfft, |i| T::from(i).unwrap() - t_mean);
+        let window = self.stft.window.view();
+        let constant = self.stft.process_complex_windowed(ones.view(), window)?;
+        let linear = self.stft.process_complex_windowed(ramp.view(), window)?;
+
+        let mut periodograms = Array2::<T>::zeros((n_segments, n_freqs));
+        self.stft.for_each_frame(
+            signal,
+            window,
+            0,
+            0..n_segments,
+            &mut self.stft.workspace(),
+            |i, spectrum| {
+                let start = i * step;
+                let (mean, slope) = trend(signal.slice(s![start..start + nperseg]), self.detrend);
+                Zip::from(periodograms.row_mut(i))
+                    .and(&spectrum)
+                    .and(constant.column(0))
+                    .and(linear.column(0))
+                    .apply(|p, &v, &c, &l| {
+                        *p = (v - c * mean - l * slope).norm_sqr() * scale;
+                    });
+            },
+        )?;
+
+        let mut psd = match self.average {
+            Average::Mean => periodograms.mean_axis(Axis(0)),
+            Average::Median => {
+                let bias = median_bias::<T>(n_segments);
+                Array1::from_shape_fn(n_freqs, |k| median(periodograms.column(k).to_vec()) / bias)
+            }
+        };
+
+        if self.onesided {
+            // Add the power of the negative frequencies
+            let two = T::from(2).unwrap();
+            let end = if self.nfft % 2 == 0 {
+                n_freqs - 1
+            } else {
+                n_freqs
+            };
+            psd.slice_mut(s![1..end]).mapv_inplace(|p| p * two);
+            Ok(psd)
+        } else {
+            // The spectrum of a real signal is symmetric
+            Ok(Array1::from_shape_fn(self.nfft, |k| {
+                psd[if k < n_freqs { k } else { self.nfft - k }]
+            }))
+        }
+    }
+}
+
+/// Estimates the power spectral density of `signal` with the defaults of `scipy.signal.welch`.
+///
+/// Returns the frequencies and the estimate.
+pub fn welch<'a, T, S>(signal: S, fs: T) -> Result<(Array1<T>, Array1<T>)>
+where
+    T: StftNum + std::fmt::Debug + std::fmt::Display + 'a,
+    S: AsArray<'a, T>,
+{
+    let welch = WelchBuilder::new().fs(fs).build()?;
+    Ok((welch.frequencies(), welch.process(signal)?))
+}
+
+/// Estimates the power spectral density of `signal` by a single periodogram with a boxcar
+/// window like `scipy.signal.periodogram`.
+///
+/// Returns the frequencies and the estimate. For other options use a `WelchBuilder` with
+/// `nperseg` equal to the signal length.
+pub fn periodogram<'a, T, S>(signal: S, fs: T) -> Result<(Array1<T>, Array1<T>)>
+where
+    T: StftNum + std::fmt::Debug + std::fmt::Display + 'a,
+    S: AsArray<'a, T>,
+{
+    let signal = signal.into();
+    let welch = WelchBuilder::new()
+        .fs(fs)
+        .window_named(windows::Window::Boxcar)
+        .nperseg(signal.len())
+        .noverlap(0)
+        .build()?;
+    Ok((welch.frequencies(), welch.process(signal)?))
+}
+
+/// Mean and slope of the trend `mean + slope * (i - (n - 1) / 2)` of `x`, which are zero for
+/// the terms not removed by `detrend`.
+fn trend<T: StftNum>(x: ArrayView1<T>, detrend: Detrend) -> (T, T) {
+    let n = T::from(x.len()).unwrap();
+    let mean = x.scalar_sum() / n;
+    match detrend {
+        Detrend::None => (T::zero(), T::zero()),
+        Detrend::Constant => (mean, T::zero()),
+        Detrend::Linear => {
+            // Least squares fit of a + b * (i - t_mean)
+            let t_mean = (n - T::one()) / T::from(2).unwrap();
+            let (mut stt, mut sty) = (T::zero(), T::zero());
+            for (i, &v) in x.iter().enumerate() {
+                let t = T::from(i).unwrap() - t_mean;
+                stt = stt + t * t;
+                sty = sty + t * (v - mean);
+            }
+            let slope = if stt > T::zero() {
+                sty / stt
+            } else {
+                T::zero()
+            };
+            (mean, slope)
+        }
+    }
+}
+
+/// Ratio of the median to the mean of a chi-squared distribution with 2 degrees of freedom
+/// for `n` samples, see `scipy.signal._spectral_py._median_bias`.
+fn median_bias<T: StftNum>(n: usize) -> T {
+    let bias = (1..=(n - 1) / 2)
+        .map(|i| {
+            let ii = 2. * i as f64;
+            1. / (ii + 1.) - 1. / ii
+        })
+        .sum::<f64>();
+    T::from(1. + bias).unwrap()
+}
+
+fn median<T: StftNum>(mut x: Vec<T>) -> T {
+    x.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    let n = x.len();
+    if n % 2 == 1 {
+        x[n / 2]
+    } else {
+        (x[n / 2 - 1] + x[n / 2]) / T::from(2).unwrap()
+    }
+}
@@ -0,0 +1,95 @@
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use rustfft::num_complex::Complex;
+use rustfft::{FFTplanner, FFT};
+
+use crate::StftNum;
+
+/// FFT of real input that only computes the one-sided spectrum.
+///
+/// For an even length `n` the real samples are packed into `n / 2` complex samples
+/// `z[j] = x[2j] + i x[2j + 1]`. After a complex FFT of half the size, the spectra of the even
+/// and odd samples are separated and combined by one more radix-2 butterfly. Odd lengths fall
+/// back to a complex FFT of size `n`.
+pub(crate) struct RealFft<T> {
+    len: usize,
+    fft: Arc<FFT<T>>,
+    twiddles: Vec<Complex<T>>,
+}
+
+impl<T: StftNum> RealFft<T> {
+    pub fn new(len: usize) -> RealFft<T> {
+        let mut planner = FFTplanner::new(false);
+        if len % 2 == 0 {
+            let half = len / 2;
+            let twiddles = (0..half)
+                .map(|k| {
+                    let phi = -2. * PI * k as f64 / len as f64;
+                    Complex::new(T::from(phi.cos()).unwrap(), T::from(phi.sin()).unwrap())
+                })
+                .collect();
+            RealFft {
+                len,
+                fft: planner.plan_fft(half),
+                twiddles,
+            }
+        } else {
+            RealFft {
+                len,
+                fft: planner.plan_fft(len),
+                twiddles: Vec::new(),
+            }
+        }
+    }
+
+    /// Length of the complex buffers required by `process()`.
+    pub fn scratch_len(&self) -> usize {
+        if self.len % 2 == 0 {
+            self.len / 2
+        } else {
+            self.len
+        }
+    }
+
+    /// Computes the `n / 2 + 1` bins of the one-sided spectrum of `input` into `output`.
+    ///
+    /// `buf_in` and `buf_out` must have a length of `scratch_len()`.
+    pub fn process(
+        &self,
+        input: &[T],
+        buf_in: &mut [Complex<T>],
+        buf_out: &mut [Complex<T>],
+        output: &mut [Complex<T>],
+    ) {
+        debug_assert_eq!(input.len(), self.len);
+        debug_assert_eq!(output.len(), self.len / 2 + 1);
+        if self.len % 2 != 0 {
+            for (b, &x) in buf_in.iter_mut().zip(input) {
+                *b = Complex::new(x, T::zero());
+            }
+            self.fft.process(buf_in, buf_out);
+            output.copy_from_slice(&buf_out[..output.len()]);
+            return;
+        }
+
+        let half = self.len / 2;
+        for (b, x) in buf_in.iter_mut().zip(input.chunks(2)) {
+            *b = Complex::new(x[0], x[1]);
+        }
+        self.fft.process(buf_in, buf_out);
+
+        let half_one = T::from(0.5).unwrap();
+        let z0 = buf_out[0];
+        output[0] = Complex::new(z0.re + z0.im, T::zero());
+        output[half] = Complex::new(z0.re - z0.im, T::zero());
+        for k in 1..half {
+            let z = buf_out[k];
+            let zc = buf_out[half - k].conj();
+            // Spectrum of the even and of the odd samples
+            let even = (z + zc) * half_one;
+            let odd = Complex::new(z.im - zc.im, zc.re - z.re) * half_one;
+            output[k] = even + self.twiddles[k] * odd;
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use std::f64::consts::PI;
+
+use ndarray::prelude::*;
+use ndarray::{AsArray, Zip};
+
+use crate::spectrum::Stft;
+use crate::windows;
+use crate::StftNum;
+
+type Result<T> = ::std::result::Result<T, Box<::std::error::Error>>;
+
+/// Time-frequency reassigned spectrogram, all arrays have the shape `[n_freqs, n_frames]`.
+pub struct ReassignedSpectrogram<T> {
+    /// Reassigned frequency of every bin in Hz.
+    pub freqs: Array2<T>,
+    /// Reassigned time of every bin in seconds.
+    pub times: Array2<T>,
+    /// Magnitude of every bin as returned by `Stft::process()` with `OutputMode::Magnitude`.
+    pub mags: Array2<T>,
+}
+
+/// Reassignment of the bins of a spectrogram to the center of gravity of their energy.
+///
+/// Frequencies are corrected by `-imag(S_dh / S_h) * sr / 2pi` and times by
+/// `real(S_th / S_h) / sr`, where `S_h`, `S_dh` and `S_th` are the STFTs with the window, its
+/// derivative and its time-weighted version. Follows `librosa.reassigned_spectrogram`.
+pub struct Reassignment<T> {
+    pub reassign_frequencies: bool,
+    pub reassign_times: bool,
+    /// Bins with a power below `ref_power` are set to NaN.
+    pub ref_power: Option<T>,
+    /// Replaces NaN by the bin frequency and frame time.
+    pub fill_nan: bool,
+    /// Clips the frequencies to `[0, sr / 2]` and the times to the signal duration.
+    pub clip: bool,
+}
+
+#[derive(Default)]
+pub struct ReassignmentBuilder<T> {
+    reassign_frequencies: Option<bool>,
+    reassign_times: Option<bool>,
+    ref_power: Option<Option<T>>,
+    fill_nan: Option<bool>,
+    clip: Option<bool>,
+}
+
+impl<T: StftNum> ReassignmentBuilder<T> {
+    pub fn new() -> ReassignmentBuilder<T> {
+        ReassignmentBuilder {
+            reassign_frequencies: None,
+            reassign_times: None,
+            ref_power: None,
+            fill_nan: None,
+            clip: None,
+        }
+    }
+    pub fn reassign_frequencies(mut self, reassign: bool) -> ReassignmentBuilder<T> {
+        self.reassign_frequencies = Some(reassign);
+        self
+    }
+    pub fn reassign_times(mut self, reassign: bool) -> ReassignmentBuilder<T> {
+        self.reassign_times = Some(reassign);
+        self
+    }
+    /// Power threshold of the reassignment, `Some(1e-6)` by default. `None` disables it.
+    pub fn ref_power(mut self, ref_power: Option<T>) -> ReassignmentBuilder<T> {
+        self.ref_power = Some(ref_power);
+        self
+    }
+    pub fn fill_nan(mut self, fill_nan: bool) -> ReassignmentBuilder<T> {
+        self.fill_nan = Some(fill_nan);
+        self
+    }
+    pub fn clip(mut self, clip: bool) -> ReassignmentBuilder<T> {
+        self.clip = Some(clip);
+        self
+    }
+    pub fn build(self) -> Result<Reassignment<T>> {
+        let ref_power = self
+            .ref_power
+            .unwrap_or_else(|| Some(T::from(1e-6).unwrap()));
+        if let Some(p) = ref_power {
+            if p < T::zero() {
+                return Err(From::from("Reassignment ref_power must be >= 0"));
+            }
+        }
+        Ok(Reassignment {
+            reassign_frequencies: self.reassign_frequencies.unwrap_or(true),
+            reassign_times: self.reassign_times.unwrap_or(true),
+            ref_power,
+            fill_nan: self.fill_nan.unwrap_or(false),
+            clip: self.clip.unwrap_or(true),
+        })
+    }
+}
+
+impl<T: StftNum + std::fmt::Debug + std::fmt::Display> Reassignment<T> {
+    /// Computes the reassigned spectrogram of `signal` sampled at `sr` using the framing,
+    /// padding and window of `stft`.
+    ///
+    /// Bins without reassignment keep the bin center frequency and the time of the frame
+    /// center.
+    pub fn process<'a, S>(
+        &self,
+        stft: &Stft<T>,
+        signal: S,
+        sr: T,
+    ) -> Result<ReassignedSpectrogram<T>>
+    where
+        S: AsArray<'a, T>,
+        T: 'a,
+    {
+        let signal = signal.into();
+        let spec = stft.process_complex_view(signal)?;
+        let (n_freqs, n_frames) = spec.dim();
+        let (n_pad_front, _) = stft.framing(signal.len())?;
+        let n_fft = T::from(stft.n_fft).unwrap();
+        let sr_over_two_pi = sr / T::from(2. * PI).unwrap();
+
+        let bin_freqs = Array1::from_shape_fn(n_freqs, |k| T::from(k).unwrap() * sr / n_fft)
+            .insert_axis(Axis(1));
+        let frame_times = Array1::from_shape_fn(n_frames, |t| {
+            let center = (t * stft.hop_length + stft.n_fft / 2) as f64 - n_pad_front as f64;
+            T::from(center).unwrap() / sr
+        })
+        .insert_axis(Axis(0));
+        let mut freqs = Array2::zeros((n_freqs, n_frames).f());
+        freqs.assign(&bin_freqs);
+        let mut times = Array2::zeros((n_freqs, n_frames).f());
+        times.assign(&frame_times);
+
+        if self.reassign_frequencies {
+            let window = windows::derivative(&stft.window);
+            let spec_dh = stft.process_complex_windowed(signal, window.view())?;
+            Zip::from(&mut freqs)
+                .and(&spec)
+                .and(&spec_dh)
+                .apply(|f, &h, &dh| *f = *f - (dh / h).im * sr_over_two_pi);
+        }
+        if self.reassign_times {
+            let window = windows::time_weighted(&stft.window);
+            let spec_th = stft.process_complex_windowed(signal, window.view())?;
+            Zip::from(&mut times)
+                .and(&spec)
+                .and(&spec_th)
+                .apply(|t, &h, &th| *t = *t + (th / h).re / sr);
+        }
+
+        let mags = spec.map(|v| v.norm_sqr().sqrt());
+        let nan = T::nan();
+        if let Some(ref_power) = self.ref_power {
+            let ref_mag = ref_power.sqrt();
+            Zip::from(&mut freqs)
+                .and(&mut times)
+                .and(&mags)
+                .apply(|f, t, &m| {
+                    if m < ref_mag {
+                        if self.reassign_frequencies {
+                            *f = nan;
+                        }
+                        if self.reassign_times {
+                            *t = nan;
+                        }
+                    }
+                });
+        }
+        if self.fill_nan {
+            Zip::from(&mut freqs)
+                .and_broadcast(&bin_freqs)
+                .apply(|f, &b| {
+                    if f.is_nan() {
+                        *f = b
+                    }
+                });
+            Zip::from(&mut times)
+                .and_broadcast(&frame_times)
+                .apply(|t, &b| {
+                    if t.is_nan() {
+                        *t = b
+                    }
+                });
+        }
+        if self.clip {
+            // NaN is kept as all comparisons are false
+            let nyquist = sr / T::from(2).unwrap();
+            let duration = T::from(signal.len()).unwrap() / sr;
+            if self.reassign_frequencies {
+                freqs.mapv_inplace(|f| clip(f, T::zero(), nyquist));
+            }
+            if self.reassign_times {
+                times.mapv_inplace(|t| clip(t, T::zero(), duration));
+            }
+        }
+        Ok(ReassignedSpectrogram { freqs, times, mags })
+    }
+}
+
+/// Computes the reassigned spectrogram with the default `Reassignment` options.
+pub fn reassigned_spectrogram<'a, T, S>(
+    stft: &Stft<T>,
+    signal: S,
+    sr: T,
+) -> Result<ReassignedSpectrogram<T>>
+where
+    T: StftNum + std::fmt::Debug + std::fmt::Display + 'a,
+    S: AsArray<'a, T>,
+{
+    ReassignmentBuilder::new()
+        .build()?
+        .process(stft, signal, sr)
+}
+
+#[inline]
+fn clip<T: StftNum>(x: T, lo: T, hi: T) -> T {
+    if x < lo {
+        lo
+    } else if x > hi {
+        hi
+    } else {
+        x
+    }
+}
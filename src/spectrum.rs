@@ -1,39 +1,112 @@
-use std::cmp::min;
-use std::iter;
+use std::cmp::{max, min};
+use std::f64::consts::PI;
+use std::ops::Range;
 use std::option::Option;
 use std::sync::Arc;
 
 use ndarray::prelude::*;
-use ndarray::Zip;
-use num_traits::Float;
+use ndarray::{AsArray, Zip};
+use num_traits::{Float, Zero};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 use rustfft::num_complex::Complex;
 use rustfft::{FFTplanner, FFT};
 
+use crate::realfft::RealFft;
+use crate::streaming::StreamingStft;
 use crate::windows;
 use crate::StftNum;
 
 type Result<T> = ::std::result::Result<T, Box<::std::error::Error>>;
 
-pub enum PadMode {
+/// Padding of the signal before it is split into frames.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PadMode<T> {
+    /// No padding, samples after the last complete frame are dropped.
     Truncate,
+    /// Pads zeros at the end to complete the last frame.
     End,
+    /// Distributes the zeros needed to complete the last frame to both sides.
     Center,
+    /// Pads `n_fft / 2` samples at both sides by reflecting the signal at its first and last
+    /// sample, repeatedly for signals shorter than the padding. Equal to
+    /// `librosa.stft(center=True, pad_mode="reflect")`.
+    Reflect,
+    /// Pads `n_fft / 2` copies of the first and last sample at both sides, also called
+    /// replicate padding.
+    Edge,
+    /// Pads `n_fft / 2` samples of a constant value at both sides. `Constant(0.)` is equal to
+    /// `librosa.stft(center=True, pad_mode="constant")`.
+    Constant(T),
 }
 
-impl Default for PadMode {
-    fn default() -> PadMode {
+impl<T> Default for PadMode<T> {
+    fn default() -> PadMode<T> {
         PadMode::Truncate
     }
 }
 
+/// Values of the spectrogram returned by `Stft::process()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputMode<T> {
+    /// `|X|`
+    Magnitude,
+    /// `|X|^p` with the exponent `p`, `Power(2.)` is the power spectrogram.
+    Power(T),
+    /// `ln(max(|X|, eps))` with the floor `eps`.
+    LogMagnitude(T),
+}
+
+impl<T> Default for OutputMode<T> {
+    fn default() -> OutputMode<T> {
+        OutputMode::Magnitude
+    }
+}
+
+/// Threads used to compute the frames of an STFT.
+#[cfg(feature = "rayon")]
+#[derive(Clone)]
+pub enum Parallelism {
+    /// Compute all frames on the calling thread.
+    Serial,
+    /// Use the global rayon thread pool.
+    Global,
+    /// Use the given rayon thread pool.
+    Pool(Arc<rayon::ThreadPool>),
+}
+
+#[cfg(feature = "rayon")]
+impl Default for Parallelism {
+    fn default() -> Parallelism {
+        Parallelism::Global
+    }
+}
+
+// Minimum number of frames computed by one parallel task
+#[cfg(feature = "rayon")]
+const MIN_FRAMES_PER_TASK: usize = 64;
+
 pub struct Stft<T> {
     pub n_fft: usize,
     pub hop_length: usize,
-    pub pad_mode: PadMode,
+    pub pad_mode: PadMode<T>,
+    pub output_mode: OutputMode<T>,
     pub window: Array1<T>,
     pub normalization: T,
-    fft: Arc<FFT<T>>,
+    fft: RealFft<T>,
+    ifft: Arc<FFT<T>>,
+    #[cfg(feature = "rayon")]
+    parallelism: Parallelism,
+}
+
+/// Scratch buffers of the STFT frame loop, see `Stft::process_into()`.
+pub struct StftWorkspace<T> {
+    window: Array1<T>,
+    frame: Array1<T>,
+    fft_input: Vec<Complex<T>>,
+    fft_output: Vec<Complex<T>>,
+    spectrum: Array1<Complex<T>>,
 }
 
 #[derive(Default)]
@@ -41,10 +114,13 @@ pub struct StftBuilder<T> {
     n_fft: Option<usize>,
     hop_length: Option<usize>,
     win_length: Option<usize>,
-    pad_mode: Option<PadMode>,
+    pad_mode: Option<PadMode<T>>,
+    output_mode: Option<OutputMode<T>>,
     window: Option<Array1<T>>,
     window_named: Option<windows::Window>,
     normalize: Option<bool>,
+    #[cfg(feature = "rayon")]
+    parallelism: Option<Parallelism>,
 }
 
 impl<T: StftNum> StftBuilder<T> {
@@ -54,9 +130,12 @@ impl<T: StftNum> StftBuilder<T> {
             hop_length: None,
             win_length: None,
             pad_mode: None,
+            output_mode: None,
             window: None,
             window_named: None,
             normalize: None,
+            #[cfg(feature = "rayon")]
+            parallelism: None,
         }
     }
     pub fn n_fft(mut self, n_fft: usize) -> StftBuilder<T> {
@@ -71,10 +150,15 @@ impl<T: StftNum> StftBuilder<T> {
         self.win_length = Some(win_length);
         self
     }
-    pub fn pad_mode(mut self, pad_mode: PadMode) -> StftBuilder<T> {
+    pub fn pad_mode(mut self, pad_mode: PadMode<T>) -> StftBuilder<T> {
         self.pad_mode = Some(pad_mode);
         self
     }
+    /// Sets the values returned by `Stft::process()`, `OutputMode::Magnitude` by default.
+    pub fn output_mode(mut self, output_mode: OutputMode<T>) -> StftBuilder<T> {
+        self.output_mode = Some(output_mode);
+        self
+    }
     pub fn window(mut self, window: Array1<T>) -> StftBuilder<T> {
         self.window = Some(window);
         self
@@ -91,6 +175,14 @@ impl<T: StftNum> StftBuilder<T> {
         self.normalize = Some(normalize);
         self
     }
+    /// Sets the threads used for the frames and channels, `Parallelism::Global` by default.
+    ///
+    /// The output does not depend on the number of threads.
+    #[cfg(feature = "rayon")]
+    pub fn parallelism(mut self, parallelism: Parallelism) -> StftBuilder<T> {
+        self.parallelism = Some(parallelism);
+        self
+    }
     pub fn build(self) -> Result<Stft<T>> {
         let n_fft = self.n_fft.unwrap_or(2048);
         let win_length = self.win_length.unwrap_or(n_fft);
@@ -99,6 +191,7 @@ impl<T: StftNum> StftBuilder<T> {
             return Err(From::from("STFT win_length must be <= n_fft"));
         }
         let pad_mode = self.pad_mode.unwrap_or_default();
+        let output_mode = self.output_mode.unwrap_or_default();
         let mut window = Array1::<T>::zeros(n_fft);
         let w_start = (n_fft - win_length) / 2;
         window
@@ -119,82 +212,617 @@ impl<T: StftNum> StftBuilder<T> {
         } else {
             T::one()
         };
-        let mut planner = FFTplanner::new(false);
-        let fft = planner.plan_fft(n_fft);
+        let fft = RealFft::new(n_fft);
+        let mut planner = FFTplanner::new(true);
+        let ifft = planner.plan_fft(n_fft);
         Ok(Stft {
             n_fft,
             hop_length,
             pad_mode,
+            output_mode,
             window,
             fft,
+            ifft,
             normalization,
+            #[cfg(feature = "rayon")]
+            parallelism: self.parallelism.unwrap_or_default(),
         })
     }
+    /// Builds a `StreamingStft` that processes the signal chunk by chunk.
+    ///
+    /// Fails for pad modes other than `PadMode::Truncate` and `PadMode::End`, which need
+    /// samples before the start of the signal.
+    pub fn build_streaming(self) -> Result<StreamingStft<T>>
+    where
+        T: std::fmt::Debug + std::fmt::Display,
+    {
+        StreamingStft::new(self.build()?)
+    }
 }
 
 impl<T: StftNum + std::fmt::Debug + std::fmt::Display> Stft<T> {
-    fn pad(&self, mut signal: Vec<T>) -> Vec<T> {
-        match self.pad_mode {
-            PadMode::Truncate => signal,
+    /// Number of padded samples in front and at the end of a signal of length `len`.
+    fn pad_lengths(&self, len: usize) -> Result<(usize, usize)> {
+        let n_half = self.n_fft / 2;
+        Ok(match self.pad_mode {
+            PadMode::Truncate | PadMode::End | PadMode::Center if len < self.n_fft => {
+                return Err(From::from("Signal must not be shorter than n_fft."));
+            }
+            PadMode::Truncate => (0, 0),
             PadMode::End => {
-                let n_pad = self.hop_length - (signal.len() - self.n_fft) % self.hop_length;
-                let new_len = signal.len() + n_pad;
-                signal.reserve(new_len);
-                for elem in iter::repeat(T::zero()).take(n_pad) {
-                    signal.push(elem);
-                }
-                signal
+                let n_pad = self.hop_length - (len - self.n_fft) % self.hop_length;
+                (0, n_pad)
             }
             PadMode::Center => {
-                let n_pad = self.hop_length - (signal.len() - self.n_fft) % self.hop_length;
+                let n_pad = self.hop_length - (len - self.n_fft) % self.hop_length;
                 let n_pad_front = n_pad / 2;
-                let n_pad_end = n_pad - n_pad_front;
-                let new_len = signal.len() + n_pad_end;
-                signal.reserve(new_len);
-                signal.splice(0..0, iter::repeat(T::zero()).take(n_pad_front));
-                for elem in iter::repeat(T::zero()).take(n_pad_end) {
-                    signal.push(elem);
+                (n_pad_front, n_pad - n_pad_front)
+            }
+            PadMode::Reflect | PadMode::Edge | PadMode::Constant(_) => {
+                if len == 0 || len + 2 * n_half < self.n_fft {
+                    return Err(From::from("Signal is too short for n_fft."));
                 }
-                signal
+                (n_half, n_half)
+            }
+        })
+    }
+
+    /// Value of the padded signal at the index `i` outside of `signal`.
+    #[inline]
+    fn pad_value(&self, signal: ArrayView1<T>, i: isize) -> T {
+        let last = signal.len() as isize - 1;
+        match self.pad_mode {
+            PadMode::Reflect if last == 0 => signal[0],
+            PadMode::Reflect => {
+                // Reflects repeatedly if the padding is longer than the signal, like numpy
+                let period = 2 * last;
+                let j = i.rem_euclid(period);
+                signal[min(j, period - j) as usize]
             }
+            PadMode::Edge => signal[if i < 0 { 0 } else { last } as usize],
+            PadMode::Constant(value) => value,
+            _ => T::zero(),
         }
     }
 
-    pub fn process(&self, signal: Vec<T>) -> Result<Array2<T>> {
-        let signal = self.pad(signal);
-        let n_frames = 1 + (signal.len() - self.n_fft) / self.hop_length;
+    /// Number of padded samples in front of the signal and number of frames.
+    pub(crate) fn framing(&self, len: usize) -> Result<(usize, usize)> {
+        let (n_pad_front, n_pad_end) = self.pad_lengths(len)?;
+        Ok((n_pad_front, self.n_frames(n_pad_front + len + n_pad_end)))
+    }
 
-        let mut fft_input = Array1::<Complex<T>>::zeros(self.n_fft);
-        let mut fft_output = Array1::<Complex<T>>::zeros(self.n_fft);
+    pub(crate) fn n_frames(&self, signal_len: usize) -> usize {
+        1 + (signal_len - self.n_fft) / self.hop_length
+    }
 
+    /// Creates the scratch buffers for `process_into()`.
+    pub fn workspace(&self) -> StftWorkspace<T> {
         let n_freqs = self.n_fft / 2 + 1;
-        let mut output = Array2::<T>::zeros((n_freqs, n_frames).f());
+        StftWorkspace {
+            window: Array1::zeros(self.n_fft),
+            frame: Array1::zeros(self.n_fft),
+            fft_input: vec![Complex::zero(); self.fft.scratch_len()],
+            fft_output: vec![Complex::zero(); self.fft.scratch_len()],
+            spectrum: Array1::zeros(n_freqs),
+        }
+    }
 
-        for frame in 0..n_frames {
+    /// Shape `[n_freqs, n_frames]` of the spectrogram of a signal with `signal_len` samples.
+    pub fn output_shape(&self, signal_len: usize) -> Result<(usize, usize)> {
+        let (_, n_frames) = self.framing(signal_len)?;
+        Ok((self.n_fft / 2 + 1, n_frames))
+    }
+
+    /// Calls `f` with the frame index and the normalized, one-sided spectrum of every frame in
+    /// `frames`.
+    ///
+    /// The padding is not copied into the signal but applied to every frame. Frame `i` starts
+    /// at sample `i * hop_length - n_pad_front` of `signal`.
+    pub(crate) fn for_each_frame<F>(
+        &self,
+        signal: ArrayView1<T>,
+        window: ArrayView1<T>,
+        n_pad_front: usize,
+        frames: Range<usize>,
+        ws: &mut StftWorkspace<T>,
+        mut f: F,
+    ) -> Result<()>
+    where
+        F: FnMut(usize, ArrayView1<Complex<T>>),
+    {
+        if ws.frame.len() != self.n_fft {
+            return Err(From::from("Stft workspace does not match n_fft."));
+        }
+        // Applying the normalization to the window saves a pass over every spectrum
+        ws.window.assign(&window);
+        ws.window.mapv_inplace(|w| w / self.normalization);
+
+        let len = signal.len() as isize;
+        let n_fft = self.n_fft as isize;
+        for frame in frames {
             // Get slice of input audio multiply it with the window
-            // and copy it to the input buffer
-            let start = frame * self.hop_length;
-            let end = min(signal.len(), start + self.n_fft);
-            Zip::from(&mut fft_input)
-                .and(&self.window)
-                .and(&signal[start..end])
-                .apply(|i, &w, &a| {
-                    *i = Complex::<T>::new(w * a, T::zero());
-                });
+            // and copy it to the frame buffer. Samples outside of the signal are padding.
+            let start = (frame * self.hop_length) as isize - n_pad_front as isize;
+            let lo = min(max(-start, 0), n_fft) as usize;
+            let hi = min(max(len - start, 0), n_fft) as usize;
+            for k in (0..lo).chain(hi..self.n_fft) {
+                ws.frame[k] = ws.window[k] * self.pad_value(signal, start + k as isize);
+            }
+            if lo < hi {
+                let offset = start + lo as isize;
+                Zip::from(ws.frame.slice_mut(s![lo..hi]))
+                    .and(ws.window.slice(s![lo..hi]))
+                    .and(signal.slice(s![offset..offset + (hi - lo) as isize]))
+                    .apply(|i, &w, &a| {
+                        *i = w * a;
+                    });
+            }
 
             // Perform FFT
             self.fft.process(
-                &mut fft_input.as_slice_mut().ok_or("Stft input is None")?,
-                &mut fft_output.as_slice_mut().ok_or("Stft output is None")?,
+                ws.frame.as_slice().ok_or("Stft frame is None")?,
+                &mut ws.fft_input,
+                &mut ws.fft_output,
+                ws.spectrum.as_slice_mut().ok_or("Stft output is None")?,
             );
 
-            // And copy onesided to the output buffer
-            output.slice_mut(s![.., frame]).assign(
-                &fft_output
-                    .slice_mut(s![0..n_freqs])
-                    .map(|v| (v / self.normalization).norm() as T),
+            f(frame, ws.spectrum.view());
+        }
+        Ok(())
+    }
+
+    /// Computes the spectrogram of shape `[n_freqs, n_frames]`.
+    ///
+    /// The values are the magnitudes of the normalized STFT, or powers or logarithms thereof as
+    /// set by `output_mode`.
+    pub fn process(&self, signal: Vec<T>) -> Result<Array2<T>> {
+        self.process_view(&signal)
+    }
+
+    /// Computes the spectrogram of a borrowed signal, e.g. a slice or an `ArrayView1`.
+    ///
+    /// The signal is not copied, also not for padding.
+    pub fn process_view<'a, S>(&self, signal: S) -> Result<Array2<T>>
+    where
+        S: AsArray<'a, T>,
+        T: 'a,
+    {
+        let signal = signal.into();
+        let (n_pad_front, n_frames) = self.framing(signal.len())?;
+        self.process_frames(signal, n_pad_front, n_frames)
+    }
+
+    /// Spectrogram of the first `n_frames` frames.
+    fn process_frames(
+        &self,
+        signal: ArrayView1<T>,
+        n_pad_front: usize,
+        n_frames: usize,
+    ) -> Result<Array2<T>> {
+        let n_freqs = self.n_fft / 2 + 1;
+        let mut output = Array2::<T>::zeros((n_freqs, n_frames).f());
+        self.spectrogram_all(vec![(signal, output.view_mut())], n_pad_front)?;
+        Ok(output)
+    }
+
+    /// Computes the spectrograms of a `[channels, samples]` signal.
+    ///
+    /// Returns an array of shape `[channels, n_freqs, n_frames]`. The FFT plan and scratch
+    /// buffers are shared by all channels.
+    pub fn process_multichannel<'a, S>(&self, signal: S) -> Result<Array3<T>>
+    where
+        S: AsArray<'a, T, Ix2>,
+        T: 'a,
+    {
+        let signal = signal.into();
+        let (n_channels, n_samples) = signal.dim();
+        let (n_pad_front, n_frames) = self.framing(n_samples)?;
+        let n_freqs = self.n_fft / 2 + 1;
+        // Keep the frames of every channel contiguous as for a single channel
+        let mut output =
+            Array3::<T>::zeros((n_channels, n_frames, n_freqs)).permuted_axes([0, 2, 1]);
+        let jobs = signal.outer_iter().zip(output.outer_iter_mut()).collect();
+        self.spectrogram_all(jobs, n_pad_front)?;
+        Ok(output)
+    }
+
+    /// Computes the spectrograms of a `[batch, channels, samples]` signal.
+    ///
+    /// Returns an array of shape `[batch, channels, n_freqs, n_frames]`.
+    pub fn process_batch<'a, S>(&self, signal: S) -> Result<Array4<T>>
+    where
+        S: AsArray<'a, T, Ix3>,
+        T: 'a,
+    {
+        let signal = signal.into();
+        let (n_batch, n_channels, n_samples) = signal.dim();
+        let (n_pad_front, n_frames) = self.framing(n_samples)?;
+        let n_freqs = self.n_fft / 2 + 1;
+        let mut output = Array3::<T>::zeros((n_batch * n_channels, n_frames, n_freqs));
+        let jobs = signal
+            .outer_iter()
+            .flat_map(|x| (0..n_channels).map(move |c| x.index_axis_move(Axis(0), c)))
+            .zip(output.outer_iter_mut().map(|out| out.reversed_axes()))
+            .collect();
+        self.spectrogram_all(jobs, n_pad_front)?;
+        Ok(output
+            .into_shape((n_batch, n_channels, n_frames, n_freqs))?
+            .permuted_axes([0, 1, 3, 2]))
+    }
+
+    /// Computes the spectrogram into `output` without allocating.
+    ///
+    /// `output` must have the shape given by `output_shape()`, `workspace` is created by
+    /// `workspace()` and can be reused for any number of calls. The frames are always computed
+    /// on the calling thread.
+    pub fn process_into<'a, S>(
+        &self,
+        signal: S,
+        output: ArrayViewMut2<T>,
+        workspace: &mut StftWorkspace<T>,
+    ) -> Result<()>
+    where
+        S: AsArray<'a, T>,
+        T: 'a,
+    {
+        let signal = signal.into();
+        let (n_pad_front, n_frames) = self.framing(signal.len())?;
+        if output.dim() != (self.n_fft / 2 + 1, n_frames) {
+            return Err(From::from("Output shape does not match output_shape()."));
+        }
+        self.spectrogram_into(signal, n_pad_front, 0, output, workspace)
+    }
+
+    /// Computes the spectrogram of the frames `first_frame..first_frame + output.shape()[1]`.
+    pub(crate) fn spectrogram_into(
+        &self,
+        signal: ArrayView1<T>,
+        n_pad_front: usize,
+        first_frame: usize,
+        mut output: ArrayViewMut2<T>,
+        workspace: &mut StftWorkspace<T>,
+    ) -> Result<()> {
+        let frames = first_frame..first_frame + output.shape()[1];
+        let window = self.window.view();
+        self.for_each_frame(
+            signal,
+            window,
+            n_pad_front,
+            frames,
+            workspace,
+            |frame, spectrum| {
+                // Copy onesided to the output buffer
+                Zip::from(output.slice_mut(s![.., frame - first_frame]))
+                    .and(&spectrum)
+                    .apply(|o, v| *o = self.output_value(v));
+            },
+        )
+    }
+
+    /// Value of the STFT bin `v` in the spectrogram, see `OutputMode`.
+    #[inline]
+    fn output_value(&self, v: &Complex<T>) -> T {
+        let norm_sqr = v.norm_sqr();
+        let two = T::from(2).unwrap();
+        match self.output_mode {
+            OutputMode::Magnitude => norm_sqr.sqrt(),
+            OutputMode::Power(p) if p == two => norm_sqr,
+            OutputMode::Power(p) => norm_sqr.powf(p / two),
+            OutputMode::LogMagnitude(eps) => norm_sqr.sqrt().max(eps).ln(),
+        }
+    }
+
+    /// Computes the spectrogram of every signal into its output.
+    #[cfg(not(feature = "rayon"))]
+    fn spectrogram_all(
+        &self,
+        jobs: Vec<(ArrayView1<T>, ArrayViewMut2<T>)>,
+        n_pad_front: usize,
+    ) -> Result<()> {
+        let mut workspace = self.workspace();
+        for (signal, output) in jobs {
+            self.spectrogram_into(signal, n_pad_front, 0, output, &mut workspace)?;
+        }
+        Ok(())
+    }
+
+    /// Computes the spectrogram of every signal into its output.
+    ///
+    /// The frames of all signals are split into chunks which are distributed over the threads.
+    /// Every thread allocates its own workspace.
+    #[cfg(feature = "rayon")]
+    fn spectrogram_all(
+        &self,
+        jobs: Vec<(ArrayView1<T>, ArrayViewMut2<T>)>,
+        n_pad_front: usize,
+    ) -> Result<()> {
+        let mut chunks = Vec::new();
+        for (signal, mut output) in jobs {
+            let mut first_frame = 0;
+            while output.shape()[1] > MIN_FRAMES_PER_TASK {
+                let (chunk, rest) = output.split_at(Axis(1), MIN_FRAMES_PER_TASK);
+                chunks.push((signal, first_frame, chunk));
+                output = rest;
+                first_frame += MIN_FRAMES_PER_TASK;
+            }
+            chunks.push((signal, first_frame, output));
+        }
+        let parallel = match self.parallelism {
+            Parallelism::Serial => false,
+            _ => chunks.len() > 1,
+        };
+        if !parallel {
+            let mut workspace = self.workspace();
+            for (signal, first_frame, chunk) in chunks {
+                self.spectrogram_into(signal, n_pad_front, first_frame, chunk, &mut workspace)?;
+            }
+            return Ok(());
+        }
+        // Box<Error> is not Send, so errors are passed as String out of the threads
+        let run = || {
+            chunks
+                .into_par_iter()
+                .map_init(
+                    || self.workspace(),
+                    |workspace, (signal, first_frame, chunk)| {
+                        self.spectrogram_into(signal, n_pad_front, first_frame, chunk, workspace)
+                            .map_err(|e| e.to_string())
+                    },
+                )
+                .collect::<::std::result::Result<(), String>>()
+        };
+        match self.parallelism {
+            Parallelism::Pool(ref pool) => pool.install(run)?,
+            _ => run()?,
+        }
+        Ok(())
+    }
+
+    /// Computes the complex spectrogram of shape `[n_freqs, n_frames]`.
+    ///
+    /// Framing, padding and normalization are the same as for `process()`, so that
+    /// `process_complex(x)?.map(|v| v.norm())` equals `process(x)?` for
+    /// `OutputMode::Magnitude`.
+    pub fn process_complex(&self, signal: Vec<T>) -> Result<Array2<Complex<T>>> {
+        self.process_complex_view(&signal)
+    }
+
+    /// Computes the complex spectrogram of a borrowed signal without copying it.
+    pub fn process_complex_view<'a, S>(&self, signal: S) -> Result<Array2<Complex<T>>>
+    where
+        S: AsArray<'a, T>,
+        T: 'a,
+    {
+        self.process_complex_windowed(signal.into(), self.window.view())
+    }
+
+    /// Complex spectrogram using `window` instead of the window of the STFT.
+    ///
+    /// `window` must have a length of `n_fft`. The normalization is not changed.
+    pub(crate) fn process_complex_windowed(
+        &self,
+        signal: ArrayView1<T>,
+        window: ArrayView1<T>,
+    ) -> Result<Array2<Complex<T>>> {
+        if window.len() != self.n_fft {
+            return Err(From::from("Window length must be equal to n_fft."));
+        }
+        let (n_pad_front, n_frames) = self.framing(signal.len())?;
+        let n_freqs = self.n_fft / 2 + 1;
+        let mut output = Array2::<Complex<T>>::zeros((n_freqs, n_frames).f());
+        self.for_each_frame(
+            signal,
+            window,
+            n_pad_front,
+            0..n_frames,
+            &mut self.workspace(),
+            |frame, spectrum| {
+                output.slice_mut(s![.., frame]).assign(&spectrum);
+            },
+        )?;
+        Ok(output)
+    }
+
+    /// Calls `f` with the frame index, the spectrum and the output column of every frame and
+    /// returns the output of shape `[n_freqs, n_frames]`.
+    fn process_frames_with<F>(&self, signal: ArrayView1<T>, mut f: F) -> Result<Array2<T>>
+    where
+        F: FnMut(usize, ArrayView1<Complex<T>>, ArrayViewMut1<T>),
+    {
+        let (n_pad_front, n_frames) = self.framing(signal.len())?;
+        let n_freqs = self.n_fft / 2 + 1;
+        let mut output = Array2::<T>::zeros((n_freqs, n_frames).f());
+        self.for_each_frame(
+            signal,
+            self.window.view(),
+            n_pad_front,
+            0..n_frames,
+            &mut self.workspace(),
+            |frame, spectrum| f(frame, spectrum, output.slice_mut(s![.., frame])),
+        )?;
+        Ok(output)
+    }
+
+    /// Computes the phase in `[-pi, pi]` of every STFT bin.
+    pub fn process_phase<'a, S>(&self, signal: S) -> Result<Array2<T>>
+    where
+        S: AsArray<'a, T>,
+        T: 'a,
+    {
+        self.process_frames_with(signal.into(), |_, spectrum, mut output| {
+            Zip::from(&mut output)
+                .and(&spectrum)
+                .apply(|o, v| *o = v.arg());
+        })
+    }
+
+    /// Computes the phase of every STFT bin, unwrapped along the time axis.
+    ///
+    /// Jumps of more than `pi` between consecutive frames are removed by adding multiples of
+    /// `2 pi`, as done by `numpy.unwrap(phase, axis=1)`.
+    pub fn process_unwrapped_phase<'a, S>(&self, signal: S) -> Result<Array2<T>>
+    where
+        S: AsArray<'a, T>,
+        T: 'a,
+    {
+        let mut prev = Array1::<Complex<T>>::zeros(self.n_fft / 2 + 1);
+        let mut phase = Array1::<T>::zeros(self.n_fft / 2 + 1);
+        self.process_frames_with(signal.into(), |frame, spectrum, mut output| {
+            Zip::from(&mut output)
+                .and(&spectrum)
+                .and(&mut prev)
+                .and(&mut phase)
+                .apply(|o, &v, p, phi| {
+                    *phi = if frame == 0 {
+                        v.arg()
+                    } else {
+                        // Only the wrapped phase difference to the previous frame is known
+                        *phi + princarg(v.arg() - p.arg())
+                    };
+                    *p = v;
+                    *o = *phi;
+                });
+        })
+    }
+
+    /// Computes the instantaneous frequency in Hz of every STFT bin.
+    ///
+    /// The frequency is estimated from the phase advance between consecutive frames, where the
+    /// deviation from the advance expected at the bin center frequency is wrapped to
+    /// `[-pi, pi]`. The first frame has no predecessor and is set to the bin center frequencies.
+    pub fn process_instantaneous_frequency<'a, S>(&self, signal: S, sr: T) -> Result<Array2<T>>
+    where
+        S: AsArray<'a, T>,
+        T: 'a,
+    {
+        let n_freqs = self.n_fft / 2 + 1;
+        let two_pi = T::from(2. * PI).unwrap();
+        let hop_length = T::from(self.hop_length).unwrap();
+        // Bin center frequencies in radians per sample
+        let omega = Array1::from_shape_fn(n_freqs, |k| {
+            two_pi * T::from(k).unwrap() / T::from(self.n_fft).unwrap()
+        });
+        let mut prev = Array1::<Complex<T>>::zeros(n_freqs);
+        self.process_frames_with(signal.into(), |frame, spectrum, mut output| {
+            Zip::from(&mut output)
+                .and(&spectrum)
+                .and(&mut prev)
+                .and(&omega)
+                .apply(|o, &v, p, &w| {
+                    let deviation = if frame == 0 {
+                        T::zero()
+                    } else {
+                        princarg(v.arg() - p.arg() - w * hop_length) / hop_length
+                    };
+                    *p = v;
+                    *o = (w + deviation) * sr / two_pi;
+                });
+        })
+    }
+
+    /// Computes the group delay of every STFT bin in samples.
+    ///
+    /// The group delay is the negative derivative of the phase along the frequency axis,
+    /// measured from the start of the frame. It is approximated by the phase difference to the
+    /// next higher bin, which determines the delay up to multiples of `n_fft`. The difference
+    /// is therefore wrapped around the frame center, giving delays in `[0, n_fft]`. The highest
+    /// bin repeats the value of the bin below.
+    pub fn process_group_delay<'a, S>(&self, signal: S) -> Result<Array2<T>>
+    where
+        S: AsArray<'a, T>,
+        T: 'a,
+    {
+        let n_freqs = self.n_fft / 2 + 1;
+        // Inverse of the frequency step between two bins in radians per sample
+        let scale = T::from(self.n_fft as f64 / (2. * PI)).unwrap();
+        let pi = T::from(PI).unwrap();
+        let center = T::from(self.n_fft as f64 / 2.).unwrap();
+        self.process_frames_with(signal.into(), |_, spectrum, mut output| {
+            for k in 0..n_freqs - 1 {
+                // Moving the time origin to the frame center adds pi to the phase difference
+                let diff = spectrum[k + 1].arg() - spectrum[k].arg() + pi;
+                output[k] = center - princarg(diff) * scale;
+            }
+            if n_freqs > 1 {
+                output[n_freqs - 1] = output[n_freqs - 2];
+            }
+        })
+    }
+
+    /// Inverse STFT of a complex spectrogram as returned by `process_complex()`.
+    ///
+    /// Each frame is transformed back, windowed again and overlap-added. The result is divided
+    /// by the sum of the squared, overlapping windows so that a spectrogram of a signal
+    /// reconstructs that signal. `length` is the length of the original signal. It is needed to
+    /// undo `PadMode::Center`; for the other modes the output is cut or zero padded to `length`
+    /// if given. The padding of `Reflect`, `Edge` and `Constant` is always removed.
+    pub fn istft(&self, spec: &Array2<Complex<T>>, length: Option<usize>) -> Result<Vec<T>> {
+        let n_freqs = self.n_fft / 2 + 1;
+        if spec.shape()[0] != n_freqs {
+            return Err(From::from(
+                "Spectrogram must have n_fft / 2 + 1 frequency bins.",
+            ));
+        }
+        let n_frames = spec.shape()[1];
+        if n_frames == 0 {
+            return Ok(vec![T::zero(); length.unwrap_or(0)]);
+        }
+        let padded_len = self.n_fft + (n_frames - 1) * self.hop_length;
+
+        let mut ifft_input = Array1::<Complex<T>>::zeros(self.n_fft);
+        let mut ifft_output = Array1::<Complex<T>>::zeros(self.n_fft);
+        let mut signal = Array1::<T>::zeros(padded_len);
+        let mut window_sum = Array1::<T>::zeros(padded_len);
+
+        let scale = self.normalization / T::from(self.n_fft).unwrap();
+        for frame in 0..n_frames {
+            // Restore the full, hermitian symmetric spectrum
+            let spectrum = spec.slice(s![.., frame]);
+            ifft_input.slice_mut(s![0..n_freqs]).assign(&spectrum);
+            for k in n_freqs..self.n_fft {
+                ifft_input[k] = spectrum[self.n_fft - k].conj();
+            }
+
+            self.ifft.process(
+                ifft_input.as_slice_mut().ok_or("Istft input is None")?,
+                ifft_output.as_slice_mut().ok_or("Istft output is None")?,
             );
+
+            // Window and overlap-add
+            let start = frame * self.hop_length;
+            Zip::from(signal.slice_mut(s![start..start + self.n_fft]))
+                .and(window_sum.slice_mut(s![start..start + self.n_fft]))
+                .and(&self.window)
+                .and(&ifft_output)
+                .apply(|y, ws, &w, &v| {
+                    *y = *y + v.re * scale * w;
+                    *ws = *ws + w * w;
+                });
         }
+        Zip::from(&mut signal).and(&window_sum).apply(|y, &ws| {
+            if ws > T::min_positive_value() {
+                *y = *y / ws;
+            }
+        });
+
+        let (start, n_pad_end) = match self.pad_mode {
+            PadMode::Truncate | PadMode::End => (0, 0),
+            PadMode::Center => match length {
+                Some(length) => (self.pad_lengths(length)?.0, 0),
+                None => {
+                    return Err(From::from(
+                        "istft() requires the signal length to undo PadMode::Center.",
+                    ))
+                }
+            },
+            PadMode::Reflect | PadMode::Edge | PadMode::Constant(_) => {
+                (self.n_fft / 2, self.n_fft / 2)
+            }
+        };
+        let length = length.unwrap_or(padded_len - start - n_pad_end);
+        let end = min(padded_len, start + length);
+        let mut output = signal.slice(s![start..end]).to_vec();
+        output.resize(length, T::zero());
         Ok(output)
     }
 }
@@ -223,3 +851,10 @@ pub fn power_to_db<T: Float>(spec: &mut Array2<T>) {
     let multiplier = T::from(10).unwrap();
     spec.mapv_inplace(|v| multiplier * v.log10());
 }
+
+/// Wraps the angle `x` to `[-pi, pi]`.
+#[inline]
+fn princarg<T: Float>(x: T) -> T {
+    let two_pi = T::from(2. * PI).unwrap();
+    x - two_pi * (x / two_pi).round()
+}
@@ -0,0 +1,122 @@
+use ndarray::prelude::*;
+
+use crate::spectrum::{PadMode, Stft, StftWorkspace};
+use crate::StftNum;
+
+type Result<T> = ::std::result::Result<T, Box<::std::error::Error>>;
+
+/// Stateful STFT for signals that arrive in chunks, e.g. from a live audio input.
+///
+/// Samples are buffered until a complete frame of `n_fft` samples is available. The
+/// concatenated output of all `push()` calls and the final `flush()` is identical to
+/// `Stft::process()` on the concatenated input.
+pub struct StreamingStft<T> {
+    stft: Stft<T>,
+    workspace: StftWorkspace<T>,
+    buffer: Vec<T>,
+    // Samples to drop because the next frame starts behind the end of the buffer (hop > n_fft)
+    n_skip: usize,
+    n_samples: usize,
+}
+
+impl<T: StftNum + std::fmt::Debug + std::fmt::Display> StreamingStft<T> {
+    /// Fails for pad modes other than `PadMode::Truncate` and `PadMode::End`.
+    pub fn new(stft: Stft<T>) -> Result<StreamingStft<T>> {
+        match stft.pad_mode {
+            PadMode::Truncate | PadMode::End => (),
+            _ => {
+                return Err(From::from(
+                    "Only PadMode::Truncate and PadMode::End are supported for streaming.",
+                ))
+            }
+        }
+        let capacity = stft.n_fft;
+        Ok(StreamingStft {
+            workspace: stft.workspace(),
+            stft,
+            buffer: Vec::with_capacity(capacity),
+            n_skip: 0,
+            n_samples: 0,
+        })
+    }
+
+    /// The underlying STFT configuration.
+    pub fn stft(&self) -> &Stft<T> {
+        &self.stft
+    }
+
+    /// Appends `samples` and returns the spectrogram of all frames completed by them.
+    ///
+    /// The output has the shape `[n_freqs, n_frames]`, where `n_frames` may be 0.
+    pub fn push(&mut self, samples: &[T]) -> Result<Array2<T>> {
+        self.n_samples += samples.len();
+        let skip = self.skip(samples.len());
+        self.buffer.extend_from_slice(&samples[skip..]);
+        self.process_buffer()
+    }
+
+    /// Consumes up to `n` of the samples that fall between two frames and returns their number.
+    fn skip(&mut self, n: usize) -> usize {
+        let skip = self.n_skip.min(n);
+        self.n_skip -= skip;
+        skip
+    }
+
+    /// Computes all complete frames of the buffer and drops the samples they consumed.
+    fn process_buffer(&mut self) -> Result<Array2<T>> {
+        let n_freqs = self.stft.n_fft / 2 + 1;
+        if self.buffer.len() < self.stft.n_fft {
+            return Ok(Array2::zeros((n_freqs, 0).f()));
+        }
+        let n_frames = self.stft.n_frames(self.buffer.len());
+        let mut output = Array2::zeros((n_freqs, n_frames).f());
+        self.stft.spectrogram_into(
+            ArrayView1::from(&self.buffer),
+            0,
+            0,
+            output.view_mut(),
+            &mut self.workspace,
+        )?;
+
+        // Keep the samples of the next incomplete frame
+        let consumed = n_frames * self.stft.hop_length;
+        if consumed > self.buffer.len() {
+            self.n_skip = consumed - self.buffer.len();
+            self.buffer.clear();
+        } else {
+            self.buffer.drain(..consumed);
+        }
+        Ok(output)
+    }
+
+    /// Finishes the signal and resets the internal state.
+    ///
+    /// For `PadMode::End` the buffered samples are padded and the last frame is returned,
+    /// otherwise the remaining samples are discarded.
+    pub fn flush(&mut self) -> Result<Array2<T>> {
+        let output = match self.stft.pad_mode {
+            PadMode::End => {
+                if self.n_samples < self.stft.n_fft {
+                    self.reset();
+                    return Err(From::from("Signal is shorter than n_fft."));
+                }
+                let n_pad = self.stft.hop_length
+                    - (self.n_samples - self.stft.n_fft) % self.stft.hop_length;
+                let skip = self.skip(n_pad);
+                let len = self.buffer.len() + n_pad - skip;
+                self.buffer.resize(len, T::zero());
+                self.process_buffer()?
+            }
+            _ => Array2::zeros((self.stft.n_fft / 2 + 1, 0).f()),
+        };
+        self.reset();
+        Ok(output)
+    }
+
+    /// Drops all buffered samples.
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+        self.n_skip = 0;
+        self.n_samples = 0;
+    }
+}
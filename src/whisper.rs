@@ -0,0 +1,88 @@
+use ndarray::prelude::*;
+use ndarray::AsArray;
+
+use crate::filters::{self, MelNorm, MelScale};
+use crate::spectrum::{OutputMode, PadMode, StftBuilder};
+use crate::windows::Window;
+use crate::StftNum;
+
+type Result<T> = ::std::result::Result<T, Box<::std::error::Error>>;
+
+/// Sample rate of the Whisper models.
+pub const SAMPLE_RATE: usize = 16000;
+pub const N_FFT: usize = 400;
+pub const HOP_LENGTH: usize = 160;
+/// Length of an input chunk in seconds.
+pub const CHUNK_LENGTH: usize = 30;
+/// Number of samples of an input chunk.
+pub const N_SAMPLES: usize = CHUNK_LENGTH * SAMPLE_RATE;
+/// Number of frames of an input chunk.
+pub const N_FRAMES: usize = N_SAMPLES / HOP_LENGTH;
+
+/// Cuts `audio` or pads it with zeros at the end to `length` samples.
+pub fn pad_or_trim<'a, T, S>(audio: S, length: usize) -> Array1<T>
+where
+    T: StftNum,
+    S: AsArray<'a, T>,
+{
+    let audio = audio.into();
+    let mut output = Array1::zeros(length);
+    let n = audio.len().min(length);
+    output.slice_mut(s![..n]).assign(&audio.slice(s![..n]));
+    output
+}
+
+/// Mel filter bank of shape `[n_mels, N_FFT / 2 + 1]` used by Whisper.
+///
+/// Equal to `librosa.filters.mel(sr=16000, n_fft=400, n_mels=n_mels)` with the Slaney mel scale
+/// and normalization. Whisper supports 80 and 128 mels.
+///
+/// The filters are the rows, as in Whisper, which is the transpose of `filters::mel_with()`.
+pub fn mel_filters<T: StftNum>(n_mels: usize) -> Result<Array2<T>> {
+    if n_mels != 80 && n_mels != 128 {
+        return Err(From::from("Whisper n_mels must be 80 or 128"));
+    }
+    let filters = filters::mel_with(
+        SAMPLE_RATE,
+        N_FFT,
+        n_mels,
+        None,
+        None,
+        MelScale::Slaney,
+        Some(MelNorm::Slaney),
+    )?;
+    Ok(filters.reversed_axes())
+}
+
+/// Log-mel spectrogram of shape `[n_mels, N_FRAMES]`, the input features of Whisper.
+///
+/// `audio` sampled at 16 kHz is padded or trimmed to 30 s. The power spectrogram of a centered
+/// STFT with a Hann window of `N_FFT` samples is projected onto `mel_filters(n_mels)`, the last
+/// frame is dropped and the `log10` is clamped to 8 below its maximum and rescaled by
+/// `(x + 4) / 4`. Follows `whisper.audio.log_mel_spectrogram(pad_or_trim(audio))`.
+pub fn log_mel_spectrogram<'a, T, S>(audio: S, n_mels: usize) -> Result<Array2<T>>
+where
+    T: StftNum + std::fmt::Debug + std::fmt::Display,
+    S: AsArray<'a, T>,
+{
+    let filters = mel_filters::<T>(n_mels)?;
+    let audio = pad_or_trim(audio, N_SAMPLES);
+    let stft = StftBuilder::new()
+        .n_fft(N_FFT)
+        .hop_length(HOP_LENGTH)
+        .pad_mode(PadMode::Reflect)
+        .window_named(Window::Hann)
+        .output_mode(OutputMode::Power(T::from(2).unwrap()))
+        .normalize(false)
+        .build()?;
+    let power = stft.process_view(&audio)?;
+
+    let mut log_spec = Array2::zeros((n_mels, N_FRAMES).f());
+    log_spec.assign(&filters.dot(&power.slice(s![.., ..N_FRAMES])));
+    let amin = T::from(1e-10).unwrap();
+    log_spec.mapv_inplace(|v| v.max(amin).log10());
+    let floor = log_spec.fold(T::neg_infinity(), |a, &b| a.max(b)) - T::from(8).unwrap();
+    let four = T::from(4).unwrap();
+    log_spec.mapv_inplace(|v| (v.max(floor) + four) / four);
+    Ok(log_spec)
+}
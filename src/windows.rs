@@ -4,9 +4,12 @@ use ndarray::{prelude::*, ScalarOperand};
 
 use crate::StftNum;
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Window {
     Hann,
     Hamming,
+    /// Rectangular window of ones
+    Boxcar,
 }
 
 impl Default for Window {
@@ -24,6 +27,7 @@ where
     match window {
         Window::Hann => hann::<T>(size, sym),
         Window::Hamming => hamming::<T>(size, sym),
+        Window::Boxcar => Array1::ones(size),
     }
 }
 
@@ -79,6 +83,242 @@ where
     general_hamming::<T>(size, T::from(0.5).unwrap(), sym)
 }
 
+/// Multiplies `window` with the time in samples relative to its center.
+///
+/// Used for the time reassignment of spectrograms, see `librosa.reassigned_spectrogram`.
+pub fn time_weighted<T>(window: &Array1<T>) -> Array1<T>
+where
+    T: StftNum,
+{
+    let size = window.len();
+    let half = (size / 2) as f64;
+    let offset = if size % 2 == 0 { 0.5 - half } else { -half };
+    Array1::from_shape_fn(size, |i| window[i] * T::from(i as f64 + offset).unwrap())
+}
+
+/// Derivative of the periodic `window` by central differences that wrap around at the edges.
+///
+/// Equal to `librosa.util.cyclic_gradient(window)`.
+pub fn derivative<T>(window: &Array1<T>) -> Array1<T>
+where
+    T: StftNum,
+{
+    let size = window.len();
+    let half_one = T::from(0.5).unwrap();
+    Array1::from_shape_fn(size, |i| {
+        (window[(i + 1) % size] - window[(i + size - 1) % size]) * half_one
+    })
+}
+
+/// Discrete prolate spheroidal sequences (Slepian tapers).
+///
+/// Returns the first `k` tapers of length `size` with the half bandwidth `nw` as rows of an
+/// array of shape `[k, size]` and their concentration ratios, the fraction of energy within
+/// the band `[-nw / size, nw / size]`. The tapers have an L2 norm of 1. Equal to
+/// `scipy.signal.windows.dpss(size, nw, k, sym, return_ratios=True)`.
+///
+/// The tapers are the eigenvectors of the largest eigenvalues of a symmetric tridiagonal
+/// matrix and are computed by bisection and inverse iteration.
+///
+/// Panics if `k > size` or `nw` is not in `(0, size / 2)`.
+pub fn dpss<T>(size: usize, nw: T, k: usize, sym: bool) -> (Array2<T>, Array1<T>)
+where
+    T: StftNum,
+{
+    let nw = nw.to_f64().unwrap();
+    assert!(k <= size, "dpss requires k <= size");
+    assert!(
+        nw > 0. && nw < size as f64 / 2.,
+        "dpss requires 0 < nw < size / 2"
+    );
+    let (size, needs_trunk) = _extend(size, sym);
+    let w = nw / size as f64;
+
+    // Tridiagonal matrix whose eigenvectors are the tapers
+    let diag = (0..size)
+        .map(|n| {
+            let t = (size as f64 - 1. - 2. * n as f64) / 2.;
+            t * t * (2. * PI * w).cos()
+        })
+        .collect::<Vec<_>>();
+    let off_diag = (1..size)
+        .map(|n| (n * (size - n)) as f64 / 2.)
+        .collect::<Vec<_>>();
+
+    let mut tapers: Vec<Vec<f64>> = Vec::with_capacity(k);
+    for i in 0..k {
+        let lambda = _tridiagonal_eigenvalue(&diag, &off_diag, size - 1 - i);
+        let mut v = _inverse_iteration(&diag, &off_diag, lambda, &tapers);
+        // Same signs as scipy: symmetric tapers have a positive sum, antisymmetric tapers start
+        // with a positive lobe
+        let flip = if i % 2 == 0 {
+            v.iter().sum::<f64>() < 0.
+        } else {
+            let thresh = (1. / size as f64).max(1e-7);
+            match v.iter().find(|x| *x * *x > thresh) {
+                Some(x) => *x < 0.,
+                None => false,
+            }
+        };
+        if flip {
+            v.iter_mut().for_each(|x| *x = -*x);
+        }
+        tapers.push(v);
+    }
+
+    // Concentration ratios from the autocorrelation of the tapers
+    let ratios = tapers
+        .iter()
+        .map(|v| {
+            (0..size)
+                .map(|lag| {
+                    let r = if lag == 0 {
+                        2. * w
+                    } else {
+                        let x = PI * 2. * w * lag as f64;
+                        4. * w * x.sin() / x
+                    };
+                    let rxx = v[..size - lag]
+                        .iter()
+                        .zip(&v[lag..])
+                        .map(|(a, b)| a * b)
+                        .sum::<f64>();
+                    rxx * r
+                })
+                .sum::<f64>()
+        })
+        .map(|r| T::from(r).unwrap())
+        .collect::<Array1<T>>();
+
+    let out_size = if needs_trunk { size - 1 } else { size };
+    let tapers = Array2::from_shape_fn((k, out_size), |(i, n)| T::from(tapers[i][n]).unwrap());
+    (tapers, ratios)
+}
+
+/// Number of eigenvalues of the symmetric tridiagonal matrix smaller than `x` (Sturm count).
+fn _sturm_count(diag: &[f64], off_diag: &[f64], x: f64) -> usize {
+    let mut count = 0;
+    let mut q = 1.;
+    for i in 0..diag.len() {
+        let e2 = if i == 0 { 0. } else { off_diag[i - 1].powi(2) };
+        q = diag[i] - x - e2 / q;
+        if q == 0. {
+            q = -f64::EPSILON * (diag[i].abs() + x.abs()).max(1.);
+        }
+        if q < 0. {
+            count += 1;
+        }
+    }
+    count
+}
+
+/// The `index`-th smallest eigenvalue of a symmetric tridiagonal matrix by bisection.
+fn _tridiagonal_eigenvalue(diag: &[f64], off_diag: &[f64], index: usize) -> f64 {
+    // Gershgorin bounds
+    let n = diag.len();
+    let radius = |i: usize| {
+        let left = if i > 0 { off_diag[i - 1].abs() } else { 0. };
+        let right = if i + 1 < n { off_diag[i].abs() } else { 0. };
+        left + right
+    };
+    let mut lo = (0..n)
+        .map(|i| diag[i] - radius(i))
+        .fold(f64::INFINITY, f64::min);
+    let mut hi = (0..n)
+        .map(|i| diag[i] + radius(i))
+        .fold(f64::NEG_INFINITY, f64::max);
+    for _ in 0..200 {
+        let mid = 0.5 * (lo + hi);
+        if mid <= lo || mid >= hi {
+            break;
+        }
+        if _sturm_count(diag, off_diag, mid) > index {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    0.5 * (lo + hi)
+}
+
+/// Eigenvector of the eigenvalue `lambda` of a symmetric tridiagonal matrix by inverse
+/// iteration, orthogonalized against the vectors in `others`.
+fn _inverse_iteration(
+    diag: &[f64],
+    off_diag: &[f64],
+    lambda: f64,
+    others: &[Vec<f64>],
+) -> Vec<f64> {
+    let n = diag.len();
+    // Perturb the shift to keep the shifted matrix regular
+    let scale = diag
+        .iter()
+        .chain(off_diag)
+        .fold(0., |a: f64, b| a.max(b.abs()));
+    let shift = lambda + 4. * f64::EPSILON * scale.max(1.);
+    let mut v = (0..n)
+        .map(|i| 1. + (i as f64 * 0.7).sin() * 0.1)
+        .collect::<Vec<f64>>();
+    for _ in 0..5 {
+        v = _solve_tridiagonal(diag, off_diag, shift, &v);
+        for u in others {
+            let dot = u.iter().zip(&v).map(|(a, b)| a * b).sum::<f64>();
+            v.iter_mut().zip(u).for_each(|(x, y)| *x -= dot * y);
+        }
+        let norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+        v.iter_mut().for_each(|x| *x /= norm);
+    }
+    v
+}
+
+/// Solves `(T - shift I) x = b` for a symmetric tridiagonal `T` by Gaussian elimination with
+/// partial pivoting.
+fn _solve_tridiagonal(diag: &[f64], off_diag: &[f64], shift: f64, b: &[f64]) -> Vec<f64> {
+    let n = diag.len();
+    // Row i has the entries a[i], c[i] and fill-in f[i] at the columns i, i + 1 and i + 2
+    let mut a = diag.iter().map(|d| d - shift).collect::<Vec<_>>();
+    let mut c = off_diag.to_vec();
+    c.push(0.);
+    let mut f = vec![0.; n];
+    let mut sub = off_diag.to_vec();
+    let mut x = b.to_vec();
+    for i in 0..n - 1 {
+        if sub[i].abs() > a[i].abs() {
+            // Swap the rows i and i + 1
+            let next_c = if i + 1 < n - 1 { c[i + 1] } else { 0. };
+            let (ai, ci) = (a[i], c[i]);
+            a[i] = sub[i];
+            c[i] = a[i + 1];
+            f[i] = next_c;
+            a[i + 1] = ci;
+            if i + 1 < n - 1 {
+                c[i + 1] = 0.;
+            }
+            sub[i] = ai;
+            x.swap(i, i + 1);
+        }
+        let m = sub[i] / a[i];
+        a[i + 1] -= m * c[i];
+        if i + 1 < n - 1 {
+            c[i + 1] -= m * f[i];
+        }
+        x[i + 1] -= m * x[i];
+    }
+    let tiny = f64::EPSILON * a.iter().fold(0., |s: f64, v| s.max(v.abs()));
+    for i in (0..n).rev() {
+        let mut s = x[i];
+        if i + 1 < n {
+            s -= c[i] * x[i + 1];
+        }
+        if i + 2 < n {
+            s -= f[i] * x[i + 2];
+        }
+        let pivot = if a[i].abs() < tiny { tiny } else { a[i] };
+        x[i] = s / pivot;
+    }
+    x
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
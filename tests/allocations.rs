@@ -0,0 +1,48 @@
+extern crate audio_featrs;
+extern crate ndarray;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use audio_featrs::{PadMode, StftBuilder};
+use ndarray::prelude::*;
+
+/// Counts all heap allocations of this test binary.
+struct CountingAllocator;
+
+static N_ALLOCS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        N_ALLOCS.fetch_add(1, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[test]
+fn test_stft_process_into_does_not_allocate() {
+    let x = (0..10000)
+        .map(|i| (i as f32 * 0.1).sin())
+        .collect::<Vec<f32>>();
+    let stft = StftBuilder::new()
+        .n_fft(1024)
+        .hop_length(256)
+        .pad_mode(PadMode::Center)
+        .build()
+        .unwrap();
+    let mut workspace = stft.workspace();
+    let mut spec = Array2::zeros(stft.output_shape(x.len()).unwrap());
+
+    let n_allocs = N_ALLOCS.load(Ordering::SeqCst);
+    for _ in 0..3 {
+        stft.process_into(&x[..], spec.view_mut(), &mut workspace)
+            .unwrap();
+    }
+    assert_eq!(N_ALLOCS.load(Ordering::SeqCst), n_allocs);
+}
@@ -0,0 +1,161 @@
+extern crate audio_featrs;
+#[macro_use]
+extern crate ndarray;
+extern crate ndarray_rand;
+extern crate rand;
+
+use std::f64::consts::PI;
+
+use audio_featrs::{filters, CqtBuilder, Window};
+use ndarray::prelude::*;
+use ndarray_rand::RandomExt;
+use rand::distributions::Normal;
+
+#[test]
+fn test_cqt_shape() {
+    let cqt = CqtBuilder::<f64>::new().build().unwrap();
+    let x = Array1::random(22050, Normal::new(0., 1.));
+    let spec = cqt.process(&x).unwrap();
+    assert_eq!(spec.dim(), (84, 1 + 22050 / 512));
+    assert_eq!(spec.dim().1, cqt.n_frames(x.len()));
+}
+
+/// Checks that a unit sinusoid at the center of bin `k` peaks in bin `k` with a magnitude of
+/// `sqrt(length) / 2`.
+fn _test_cqt_sine(builder: CqtBuilder<f64>, bins: &[usize]) {
+    let cqt = builder.build().unwrap();
+    let (sr, fmin) = (cqt.sr, cqt.fmin);
+    for &k in bins {
+        let freq = fmin * 2f64.powf(k as f64 / 12.);
+        let x = Array1::from_shape_fn((3. * sr) as usize, |i| {
+            (2. * PI * freq * i as f64 / sr).cos()
+        });
+        let spec = cqt.process(&x).unwrap();
+        let frame = spec.column(spec.dim().1 / 2);
+        let peak = frame
+            .iter()
+            .enumerate()
+            .fold(0, |m, (i, &v)| if v > frame[m] { i } else { m });
+        assert_eq!(peak, k);
+        let gain = frame[k] / cqt.lengths[k].sqrt();
+        assert!((gain - 0.5).abs() < 0.02, "bin {}: {} !~ 0.5", k, gain);
+    }
+}
+
+#[test]
+fn test_cqt_sine() {
+    _test_cqt_sine(CqtBuilder::new(), &[0, 11, 12, 40, 83]);
+}
+
+#[test]
+fn test_cqt_sine_early_downsample() {
+    _test_cqt_sine(CqtBuilder::new().sr(44100.), &[0, 30, 83]);
+}
+
+#[test]
+fn test_cqt_sine_high_octave() {
+    // The highest octave is close to Nyquist and computed without resampling
+    _test_cqt_sine(CqtBuilder::new().fmin(330.).n_bins(60), &[0, 25, 50, 59]);
+}
+
+#[test]
+fn test_vqt_sine() {
+    let alpha = 2f64.powf(1. / 12.) - 1.;
+    _test_cqt_sine(CqtBuilder::new().gamma(10.), &[0, 11, 12, 40, 83]);
+    _test_cqt_sine(CqtBuilder::new().gamma(24.7 * alpha / 0.108), &[0, 30, 83]);
+}
+
+#[test]
+fn test_vqt_lengths() {
+    let sr = 22050.;
+    let fmin = 32.703;
+    let alpha = 2f64.powf(1. / 12.) - 1.;
+    let cqt = filters::constant_q_lengths(sr, fmin, 84, 12, Window::Hann, 1., 0.).unwrap();
+    let vqt = filters::constant_q_lengths(sr, fmin, 84, 12, Window::Hann, 1., 20.).unwrap();
+    for (k, (&l_cqt, &l_vqt)) in cqt.iter().zip(vqt.iter()).enumerate() {
+        let freq = fmin * 2f64.powf(k as f64 / 12.);
+        // The bandwidth alpha * f is widened by gamma
+        let ratio = l_cqt / l_vqt;
+        assert!((ratio - (alpha * freq + 20.) / (alpha * freq)).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn test_cqt_hybrid() {
+    let x = Array1::random(22050, Normal::new(0., 1.));
+    let cqt = CqtBuilder::new().build().unwrap();
+    let hybrid = CqtBuilder::new().hybrid(true).build().unwrap();
+    let spec = cqt.process(&x).unwrap();
+    let spec_hybrid = hybrid.process(&x).unwrap();
+    assert_eq!(spec.dim(), spec_hybrid.dim());
+    assert!(hybrid.process_complex(&x).is_err());
+
+    // The bins with filters longer than two hops are a CQT of fewer bins
+    let n_full = cqt
+        .lengths
+        .iter()
+        .filter(|&&l| 2f64.powf(l.log2().ceil()) >= 1024.)
+        .count();
+    assert!(n_full > 0 && n_full < 84);
+    let full = CqtBuilder::new()
+        .n_bins(n_full)
+        .build()
+        .unwrap()
+        .process(&x)
+        .unwrap();
+    assert!(full.all_close(&spec_hybrid.slice(s![..n_full, ..]), 1e-12));
+    assert!(!spec.all_close(&spec_hybrid, 1e-3));
+}
+
+#[test]
+fn test_cqt_hybrid_sine() {
+    // All bins are computed from the STFT
+    let cqt = CqtBuilder::new()
+        .fmin(1000.)
+        .n_bins(36)
+        .hybrid(true)
+        .build()
+        .unwrap();
+    let sr = cqt.sr;
+    for &k in &[0, 20, 35] {
+        let freq = 1000. * 2f64.powf(k as f64 / 12.);
+        let x = Array1::from_shape_fn(8192, |i| (2. * PI * freq * i as f64 / sr).cos());
+        let spec = cqt.process(&x).unwrap();
+        let frame = spec.column(spec.dim().1 / 2);
+        let peak = frame
+            .iter()
+            .enumerate()
+            .fold(0, |m, (i, &v)| if v > frame[m] { i } else { m });
+        assert_eq!(peak, k);
+    }
+}
+
+#[test]
+fn test_cqt_unscaled() {
+    let sr = 22050.;
+    let x = Array1::random(8192, Normal::new(0., 1.));
+    let scaled = CqtBuilder::new().sr(sr).build().unwrap();
+    let unscaled = CqtBuilder::new().sr(sr).scale(false).build().unwrap();
+    let spec = scaled.process(&x).unwrap();
+    let spec_unscaled = unscaled.process(&x).unwrap();
+    for ((row, row_unscaled), &length) in spec
+        .outer_iter()
+        .zip(spec_unscaled.outer_iter())
+        .zip(scaled.lengths.iter())
+    {
+        for (&a, &b) in row.iter().zip(row_unscaled.iter()) {
+            assert!((a * length.sqrt() - b).abs() <= 1e-9 * b.abs().max(1.));
+        }
+    }
+}
+
+#[test]
+fn test_cqt_build_errors() {
+    // 7 octaves require a hop length divisible by 2^6
+    assert!(CqtBuilder::<f64>::new().hop_length(100).build().is_err());
+    assert!(CqtBuilder::<f64>::new().hop_length(64).build().is_ok());
+    // The highest bin is beyond Nyquist
+    assert!(CqtBuilder::<f64>::new().fmin(440.).build().is_err());
+    assert!(CqtBuilder::<f64>::new().sparsity(1.).build().is_err());
+    assert!(CqtBuilder::<f64>::new().gamma(-1.).build().is_err());
+}
@@ -0,0 +1,114 @@
+extern crate audio_featrs;
+#[macro_use]
+extern crate ndarray;
+
+use std::f64::consts::PI;
+
+use audio_featrs::filters::{self, Norm};
+use audio_featrs::{features, OutputMode, PadMode, StftBuilder};
+use ndarray::prelude::*;
+
+fn _power_spectrogram(x: &Array1<f64>, n_fft: usize) -> Array2<f64> {
+    StftBuilder::new()
+        .n_fft(n_fft)
+        .hop_length(512)
+        .pad_mode(PadMode::Reflect)
+        .output_mode(OutputMode::Power(2.))
+        .normalize(false)
+        .build()
+        .unwrap()
+        .process_view(x)
+        .unwrap()
+}
+
+fn _sine(freq: f64, sr: f64, size: usize) -> Array1<f64> {
+    Array1::from_shape_fn(size, |i| (2. * PI * freq * i as f64 / sr).sin())
+}
+
+#[test]
+fn test_chroma_filter() {
+    let fb = filters::chroma::<f64>(22050., 2048, 12, 0., 5., None, Some(Norm::L2)).unwrap();
+    assert_eq!(fb.dim(), (12, 1025));
+    for column in fb.axis_iter(Axis(1)) {
+        let norm = column.dot(&column).sqrt();
+        assert!((norm - 1.).abs() < 1e-12);
+    }
+    let fb = filters::chroma(22050., 2048, 24, 0., 5., Some(2.), Some(Norm::Max)).unwrap();
+    assert_eq!(fb.dim(), (24, 1025));
+    assert!(fb.iter().all(|&v| (0. ..=1.).contains(&v)));
+    assert!(filters::chroma(22050., 2048, 0, 0., 5., None, None).is_err());
+}
+
+#[test]
+fn test_chroma_stft_pitch_class() {
+    let sr = 22050.;
+    // A4, C5 and a quarter tone above A4 with the matching tuning
+    for &(freq, tuning, class) in &[(440., 0., 9), (523.25, 0., 0), (452.89, 0.5, 9)] {
+        let spec = _power_spectrogram(&_sine(freq, sr, 22050), 2048);
+        let chroma = features::chroma_stft(&spec, sr, 2048, 12, tuning, Some(Norm::Max)).unwrap();
+        assert_eq!(chroma.dim(), (12, spec.dim().1));
+        let frame = chroma.column(chroma.dim().1 / 2);
+        assert!((frame[class] - 1.).abs() < 1e-12);
+        for (c, &v) in frame.iter().enumerate() {
+            if c != class {
+                assert!(v < 0.5, "{} Hz, class {}: {}", freq, c, v);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_chroma_stft_shape_error() {
+    let spec = Array2::zeros((513, 10));
+    assert!(features::chroma_stft(&spec, 22050., 2048, 12, 0., None).is_err());
+}
+
+#[test]
+fn test_chroma_cens() {
+    let sr = 22050.;
+    let spec = _power_spectrogram(&_sine(440., sr, 44100), 2048);
+    let cens = features::chroma_cens(&spec, sr, 2048, 12, 0., Some(41), Some(Norm::L2)).unwrap();
+    assert_eq!(cens.dim(), (12, spec.dim().1));
+    for frame in cens.axis_iter(Axis(1)) {
+        assert!((frame.dot(&frame) - 1.).abs() < 1e-12);
+        let peak = frame.iter().cloned().fold(0., f64::max);
+        assert_eq!(frame[9], peak);
+    }
+
+    // Without smoothing, the values before the normalization are the quantization levels
+    let cens = features::chroma_cens(&spec, sr, 2048, 12, 0., None, None).unwrap();
+    assert!(cens.iter().all(|&v| [0., 0.25, 0.5, 0.75, 1.].contains(&v)));
+    // The padded edge frames aside, the pitch class holds more than 40 % of the energy
+    let n_frames = cens.dim().1;
+    assert!(cens.slice(s![9, 1..n_frames - 1]).iter().all(|&v| v == 1.));
+}
+
+#[test]
+fn test_tonnetz_pitch_class() {
+    let sr = 22050.;
+    // Tonal centroid of a single pitch class
+    let centroid = |c: usize| {
+        let pitch = c as f64;
+        arr1(&[
+            (7. / 6. * pitch * PI - PI / 2.).cos(),
+            (7. / 6. * pitch * PI).cos(),
+            (1.5 * pitch * PI - PI / 2.).cos(),
+            (1.5 * pitch * PI).cos(),
+            0.5 * (2. / 3. * pitch * PI - PI / 2.).cos(),
+            0.5 * (2. / 3. * pitch * PI).cos(),
+        ])
+    };
+    for &(freq, class) in &[(440., 9), (261.63, 0), (392., 7)] {
+        let spec = _power_spectrogram(&_sine(freq, sr, 22050), 4096);
+        let tonnetz = features::tonnetz(&spec, sr, 4096, 12, 0.).unwrap();
+        assert_eq!(tonnetz.dim(), (6, spec.dim().1));
+        let frame = tonnetz.column(tonnetz.dim().1 / 2);
+        let nearest = (0..12)
+            .map(|c| (c, (&centroid(c) - &frame).mapv(|v| v * v).scalar_sum()))
+            .fold(
+                (0, f64::INFINITY),
+                |m, d| if d.1 < m.1 { d } else { m },
+            );
+        assert_eq!(nearest.0, class);
+    }
+}
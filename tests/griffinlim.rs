@@ -0,0 +1,97 @@
+extern crate audio_featrs;
+extern crate ndarray;
+
+use audio_featrs::{GriffinLimBuilder, PadMode, PhaseInit, StftBuilder};
+use ndarray::prelude::*;
+use std::f64::consts::PI;
+
+fn _chirp(size: usize) -> Vec<f64> {
+    Array1::linspace(0., 1., size)
+        .mapv(|t| (2. * PI * (200. + 400. * t) * t).sin() + 0.5 * (2. * PI * 30. * t).sin())
+        .to_vec()
+}
+
+fn _test_griffinlim(momentum: f64, pad: PadMode<f64>) -> Vec<f64> {
+    let size = 4000;
+    let x = _chirp(size);
+    let stft = StftBuilder::<f64>::new()
+        .n_fft(256)
+        .hop_length(64)
+        .pad_mode(pad)
+        .build()
+        .unwrap();
+    let mag = stft.process(x).unwrap();
+    let gl = GriffinLimBuilder::new()
+        .n_iter(50)
+        .momentum(momentum)
+        .init(PhaseInit::Zeros)
+        .length(size)
+        .build()
+        .unwrap();
+    let (y, convergence) = gl.process(&stft, &mag).unwrap();
+    assert_eq!(y.len(), size);
+    assert_eq!(convergence.len(), 50);
+    convergence
+}
+
+#[test]
+fn test_griffinlim_converges() {
+    let convergence = _test_griffinlim(0., PadMode::Center);
+    // Griffin-Lim is guaranteed to not increase the inconsistency
+    for w in convergence.windows(2) {
+        assert!(w[1] <= w[0] + 1e-12, "{:?}", convergence);
+    }
+    assert!(convergence[49] < 0.5 * convergence[0], "{:?}", convergence);
+}
+
+#[test]
+fn test_fast_griffinlim_converges() {
+    let convergence = _test_griffinlim(0.99, PadMode::End);
+    let convergence_slow = _test_griffinlim(0., PadMode::End);
+    assert!(convergence[49] < convergence_slow[49]);
+}
+
+#[test]
+fn test_griffinlim_random_init() {
+    let stft = StftBuilder::<f32>::new()
+        .n_fft(64)
+        .hop_length(16)
+        .build()
+        .unwrap();
+    let x = _chirp(1000).iter().map(|&v| v as f32).collect();
+    let mag = stft.process(x).unwrap();
+    let gl = GriffinLimBuilder::new().n_iter(5).build().unwrap();
+    let (y, convergence) = gl.process(&stft, &mag).unwrap();
+    assert_eq!(y.len(), 64 + (mag.shape()[1] - 1) * 16);
+    assert!(convergence.iter().all(|v| v.is_finite()));
+}
+
+#[test]
+fn test_griffinlim_seed() {
+    let stft = StftBuilder::<f64>::new()
+        .n_fft(64)
+        .hop_length(16)
+        .build()
+        .unwrap();
+    let mag = stft.process(_chirp(1000)).unwrap();
+    let process = |seed| {
+        GriffinLimBuilder::new()
+            .n_iter(3)
+            .seed(seed)
+            .build()
+            .unwrap()
+            .process(&stft, &mag)
+            .unwrap()
+            .0
+    };
+    assert_eq!(process(7), process(7));
+    assert_ne!(process(7), process(8));
+}
+
+#[test]
+fn test_griffinlim_negative_momentum() {
+    assert!(GriffinLimBuilder::<f32>::new()
+        .momentum(-1.)
+        .build()
+        .is_err());
+}
@@ -0,0 +1,90 @@
+extern crate audio_featrs;
+extern crate ndarray;
+extern crate ndarray_rand;
+extern crate rand;
+
+use std::f64::consts::PI;
+
+use audio_featrs::filters::{self, MelNorm, MelScale};
+use audio_featrs::{
+    mel_to_audio, mel_to_stft, GriffinLimBuilder, OutputMode, PhaseInit, StftBuilder,
+};
+use ndarray::prelude::*;
+use ndarray_rand::RandomExt;
+use rand::distributions::Uniform;
+
+fn _basis(n_fft: usize, n_mels: usize) -> Array2<f64> {
+    let norm = Some(MelNorm::Slaney);
+    filters::mel_with(16000, n_fft, n_mels, None, None, MelScale::Slaney, norm).unwrap()
+}
+
+#[test]
+fn test_mel_to_stft_reprojection() {
+    let basis = _basis(512, 40);
+    let magnitude = Array2::random((257, 20), Uniform::new(0., 1.));
+    let mel = basis.t().dot(&magnitude.mapv(|v| v * v));
+
+    let estimate = mel_to_stft(&mel, &basis, 2.).unwrap();
+    assert_eq!(estimate.dim(), (257, 20));
+    assert!(estimate.iter().all(|&v| v >= 0.));
+    // A non-negative solution exists, so the mel spectrogram is matched
+    let reprojected = basis.t().dot(&estimate.mapv(|v| v * v));
+    let error = (&reprojected - &mel).mapv(|v| v * v).scalar_sum().sqrt();
+    let norm = mel.mapv(|v| v * v).scalar_sum().sqrt();
+    assert!(error < 1e-3 * norm);
+}
+
+#[test]
+fn test_mel_to_stft_pseudo_inverse() {
+    // Flat spectra have a non-negative minimum norm solution
+    let basis = _basis(512, 40);
+    let mel = basis.t().dot(&Array2::ones((257, 3)));
+    let estimate = mel_to_stft(&mel, &basis, 1.).unwrap();
+    assert!(estimate.iter().all(|&v| v >= 0.));
+    // Exact up to rounding, without the tolerance of the iterative refinement
+    assert!(basis.t().dot(&estimate).all_close(&mel, 1e-9));
+    // Identical frames have identical solutions
+    assert!(estimate.column(0).all_close(&estimate.column(2), 1e-12));
+
+    let zeros = mel_to_stft(&Array2::zeros((40, 2)), &basis, 2.).unwrap();
+    assert!(zeros.iter().all(|&v| v == 0.));
+
+    assert!(mel_to_stft(&Array2::zeros((30, 2)), &basis, 2.).is_err());
+    assert!(mel_to_stft(&mel, &basis, 0.).is_err());
+}
+
+#[test]
+fn test_mel_to_audio() {
+    let size = 4000;
+    let x = Array1::from_shape_fn(size, |i| (2. * PI * 1000. * i as f64 / 16000.).sin());
+    let stft = || StftBuilder::<f64>::new().n_fft(512).hop_length(128);
+    let power = stft()
+        .output_mode(OutputMode::Power(2.))
+        .build()
+        .unwrap()
+        .process_view(&x)
+        .unwrap();
+    let basis = _basis(512, 64);
+    let mel = basis.t().dot(&power);
+
+    let gl = GriffinLimBuilder::new()
+        .n_iter(20)
+        .init(PhaseInit::Zeros)
+        .length(size)
+        .build()
+        .unwrap();
+    let stft = stft().build().unwrap();
+    let y = mel_to_audio(&mel, &basis, 2., &stft, &gl).unwrap();
+    assert_eq!(y.len(), size);
+
+    // The tone is at the 32nd bin
+    let magnitude = stft.process(y).unwrap();
+    let spectrum = magnitude.sum_axis(Axis(1));
+    let peak = spectrum
+        .iter()
+        .enumerate()
+        .fold(0, |m, (i, &v)| if v > spectrum[m] { i } else { m });
+    assert!((peak as isize - 32).abs() <= 1);
+
+    assert!(mel_to_audio(&mel, &_basis(1024, 64), 2., &stft, &gl).is_err());
+}
@@ -0,0 +1,98 @@
+extern crate audio_featrs;
+extern crate ndarray;
+extern crate ndarray_rand;
+extern crate num_traits;
+extern crate rand;
+
+use audio_featrs::{PadMode, StftBuilder, Window};
+use ndarray::prelude::*;
+use ndarray_rand::RandomExt;
+use num_traits::Float;
+use rand::distributions::Normal;
+use std::fmt::Debug;
+
+fn assert_close<F>(a: &[F], b: &[F], delta: F)
+where
+    F: Float + Debug,
+{
+    assert_eq!(a.len(), b.len());
+    for (i, (&x, &y)) in a.iter().zip(b).enumerate() {
+        assert!((x - y).abs() <= delta, "{:?} !~ {:?} at pos {}", x, y, i);
+    }
+}
+
+fn _test_roundtrip(
+    size: usize,
+    n_fft: usize,
+    hop_length: usize,
+    pad: PadMode<f64>,
+    normalize: bool,
+) {
+    let x = Array1::random(size, Normal::new(0., 1.)).to_vec();
+    let stft = StftBuilder::<f64>::new()
+        .n_fft(n_fft)
+        .hop_length(hop_length)
+        .window_named(Window::Hamming)
+        .normalize(normalize)
+        .pad_mode(pad)
+        .build()
+        .unwrap();
+    let spec = stft.process_complex(x.clone()).unwrap();
+    let y = stft.istft(&spec, Some(size)).unwrap();
+    // Truncate drops the samples after the last complete frame
+    let n = match stft.pad_mode {
+        PadMode::Truncate => n_fft + (spec.shape()[1] - 1) * hop_length,
+        _ => size,
+    };
+    assert_close(&y[..n], &x[..n], 1e-10);
+}
+
+#[test]
+fn test_istft_roundtrip_truncate() {
+    _test_roundtrip(4000, 1024, 256, PadMode::Truncate, true);
+}
+#[test]
+fn test_istft_roundtrip_end() {
+    _test_roundtrip(4000, 1024, 441, PadMode::End, true);
+}
+#[test]
+fn test_istft_roundtrip_center() {
+    _test_roundtrip(4000, 512, 128, PadMode::Center, false);
+}
+#[test]
+fn test_istft_roundtrip_odd_n_fft() {
+    _test_roundtrip(1000, 101, 25, PadMode::Center, true);
+}
+
+#[test]
+fn test_istft_roundtrip_reflect() {
+    _test_roundtrip(4000, 512, 128, PadMode::Reflect, true);
+    _test_roundtrip(1000, 101, 25, PadMode::Edge, false);
+    _test_roundtrip(1000, 256, 64, PadMode::Constant(0.5), true);
+}
+#[test]
+fn test_istft_reflect_removes_padding() {
+    let stft = StftBuilder::<f64>::new()
+        .n_fft(512)
+        .hop_length(100)
+        .pad_mode(PadMode::Reflect)
+        .build()
+        .unwrap();
+    let x = Array1::random(4000, Normal::new(0., 1.)).to_vec();
+    let y = stft
+        .istft(&stft.process_complex(x.clone()).unwrap(), None)
+        .unwrap();
+    assert_close(&y, &x, 1e-10);
+}
+
+#[test]
+fn test_istft_center_requires_length() {
+    let stft = StftBuilder::<f32>::new()
+        .n_fft(16)
+        .hop_length(4)
+        .pad_mode(PadMode::Center)
+        .build()
+        .unwrap();
+    let spec = stft.process_complex(vec![0.; 100]).unwrap();
+    assert!(stft.istft(&spec, None).is_err());
+}
@@ -0,0 +1,203 @@
+extern crate audio_featrs;
+#[macro_use]
+extern crate ndarray;
+extern crate ndarray_rand;
+extern crate rand;
+
+use audio_featrs::{filters, kaldi};
+use ndarray::prelude::*;
+use ndarray_rand::RandomExt;
+use rand::distributions::Normal;
+
+fn _frame_opts() -> kaldi::FrameExtractionOptions<f64> {
+    kaldi::FrameExtractionOptions {
+        dither: 0.,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_kaldi_num_frames() {
+    let x = Array1::random(16000, Normal::new(0., 1.));
+    let mut opts = kaldi::SpectrogramOptions {
+        frame_opts: _frame_opts(),
+        ..Default::default()
+    };
+    assert_eq!(opts.frame_opts.window_size(), 400);
+    assert_eq!(opts.frame_opts.window_shift(), 160);
+    assert_eq!(opts.frame_opts.padded_window_size(), 512);
+    let spec = kaldi::spectrogram(&x, &opts).unwrap();
+    assert_eq!(spec.dim(), (257, 1 + (16000 - 400) / 160));
+
+    opts.frame_opts.snip_edges = false;
+    opts.frame_opts.round_to_power_of_two = false;
+    let spec = kaldi::spectrogram(&x, &opts).unwrap();
+    assert_eq!(spec.dim(), (201, 100));
+    assert_eq!(opts.frame_opts.num_frames(x.len()), 100);
+
+    // Without snip_edges, signals shorter than a frame still give frames
+    for &n in &[0, 79, 80, 300] {
+        let x = Array1::random(n, Normal::new(0., 1.));
+        let spec = kaldi::spectrogram(&x, &opts).unwrap();
+        assert_eq!(spec.dim(), (201, (n + 80) / 160));
+        assert!(spec.iter().all(|v| v.is_finite()));
+    }
+}
+
+#[test]
+fn test_kaldi_mel_banks() {
+    let opts = kaldi::MelBanksOptions::default();
+    let banks = kaldi::mel_banks::<f64>(&opts, 16000., 512).unwrap();
+    assert_eq!(banks.dim(), (23, 257));
+    assert!(banks.column(256).iter().all(|&v| v == 0.));
+    for bank in banks.outer_iter() {
+        let peak = bank.fold(0., |a: f64, &b| a.max(b));
+        assert!(peak > 0.5 && peak <= 1.);
+    }
+    // Nothing below low_freq
+    assert!(banks
+        .slice(s![.., ..=20 * 512 / 16000])
+        .iter()
+        .all(|&v| v == 0.));
+
+    let opts = kaldi::MelBanksOptions {
+        num_bins: 3,
+        ..Default::default()
+    };
+    assert!(kaldi::mel_banks::<f64>(&opts, 16000., 512).is_err());
+}
+
+#[test]
+fn test_kaldi_energy() {
+    let x = Array1::random(4000, Normal::new(0., 1.));
+    let opts = kaldi::FbankOptions {
+        frame_opts: _frame_opts(),
+        use_energy: true,
+        ..Default::default()
+    };
+    let fbank = kaldi::fbank(&x, &opts).unwrap();
+    assert_eq!(fbank.dim().0, 24);
+    // The raw energy is computed after the removal of the DC offset
+    for (i, &energy) in fbank.row(0).iter().enumerate() {
+        let frame = x.slice(s![i * 160..i * 160 + 400]);
+        let frame = &frame - frame.scalar_sum() / 400.;
+        assert!((energy - frame.dot(&frame).ln()).abs() < 1e-9);
+    }
+
+    let htk = kaldi::fbank(
+        &x,
+        &kaldi::FbankOptions {
+            htk_compat: true,
+            ..opts.clone()
+        },
+    )
+    .unwrap();
+    assert!(htk.row(23).all_close(&fbank.row(0), 1e-12));
+    assert!(htk
+        .slice(s![..23, ..])
+        .all_close(&fbank.slice(s![1.., ..]), 1e-12));
+
+    let spec_opts = kaldi::SpectrogramOptions {
+        frame_opts: _frame_opts(),
+        ..Default::default()
+    };
+    let spec = kaldi::spectrogram(&x, &spec_opts).unwrap();
+    assert!(spec.row(0).all_close(&fbank.row(0), 1e-12));
+}
+
+#[test]
+fn test_kaldi_silence() {
+    // Silent frames are floored at ln(f32::EPSILON) like torchaudio, also for f64
+    let mut x = Array1::random(4000, Normal::new(0., 1.));
+    x.slice_mut(s![..800]).fill(0.);
+    let floor = (f32::EPSILON as f64).ln();
+    assert!((floor + 15.942).abs() < 1e-3);
+
+    let spec_opts = kaldi::SpectrogramOptions {
+        frame_opts: _frame_opts(),
+        ..Default::default()
+    };
+    let spec = kaldi::spectrogram(&x, &spec_opts).unwrap();
+    let fbank_opts = kaldi::FbankOptions {
+        frame_opts: _frame_opts(),
+        use_energy: true,
+        ..Default::default()
+    };
+    let fbank = kaldi::fbank(&x, &fbank_opts).unwrap();
+    let mfcc_opts = kaldi::MfccOptions {
+        frame_opts: _frame_opts(),
+        ..Default::default()
+    };
+    let mfcc = kaldi::mfcc(&x, &mfcc_opts).unwrap();
+    // The first 3 frames are all zeros
+    for t in 0..3 {
+        assert!(spec.column(t).iter().all(|&v| (v - floor).abs() < 1e-12));
+        assert!(fbank.column(t).iter().all(|&v| (v - floor).abs() < 1e-12));
+        assert!((mfcc[[0, t]] - floor).abs() < 1e-12);
+    }
+    assert!(spec.column(4).iter().all(|&v| v > floor));
+}
+
+#[test]
+fn test_kaldi_mfcc() {
+    let x = Array1::random(8000, Normal::new(0., 1.));
+    let fbank_opts = kaldi::FbankOptions {
+        frame_opts: _frame_opts(),
+        ..Default::default()
+    };
+    let fbank = kaldi::fbank(&x, &fbank_opts).unwrap();
+    let opts = kaldi::MfccOptions {
+        frame_opts: _frame_opts(),
+        use_energy: false,
+        cepstral_lifter: 0.,
+        ..Default::default()
+    };
+    let mfcc = kaldi::mfcc(&x, &opts).unwrap();
+    assert_eq!(mfcc.dim(), (13, fbank.dim().1));
+    assert!(mfcc.all_close(&filters::dct(13, 23).dot(&fbank), 1e-9));
+
+    // c0 is moved to the end and scaled
+    let htk = kaldi::mfcc(
+        &x,
+        &kaldi::MfccOptions {
+            htk_compat: true,
+            ..opts.clone()
+        },
+    )
+    .unwrap();
+    assert!(htk
+        .slice(s![..12, ..])
+        .all_close(&mfcc.slice(s![1.., ..]), 1e-12));
+    assert!(htk.row(12).all_close(&(&mfcc.row(0) * 2f64.sqrt()), 1e-12));
+}
+
+#[test]
+fn test_kaldi_dither() {
+    let x = Array1::random(4000, Normal::new(0., 1.));
+    let opts = kaldi::FbankOptions::default();
+    assert_eq!(opts.frame_opts.dither, 1.);
+    let a = kaldi::fbank(&x, &opts).unwrap();
+    let b = kaldi::fbank(&x, &opts).unwrap();
+    assert!(!a.all_close(&b, 1e-9));
+}
+
+#[test]
+fn test_kaldi_errors() {
+    let x = Array1::random(300, Normal::new(0., 1.));
+    // Shorter than one frame
+    assert!(kaldi::fbank(&x, &kaldi::FbankOptions::default()).is_err());
+    let x = Array1::random(4000, Normal::new(0., 1.));
+    let opts = kaldi::MfccOptions {
+        num_ceps: 24,
+        ..Default::default()
+    };
+    assert!(kaldi::mfcc(&x, &opts).is_err());
+    let opts = kaldi::FbankOptions {
+        frame_opts: kaldi::FrameExtractionOptions {
+            preemph_coeff: 1.5,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    assert!(kaldi::fbank(&x, &opts).is_err());
+}
@@ -0,0 +1,170 @@
+extern crate audio_featrs;
+#[macro_use]
+extern crate ndarray;
+extern crate ndarray_rand;
+extern crate rand;
+
+use audio_featrs::filters::{self, MelNorm, MelScale};
+use audio_featrs::{MfccBuilder, PadMode, StftBuilder};
+use ndarray::prelude::*;
+use ndarray_rand::RandomExt;
+use rand::distributions::Normal;
+
+fn _stft() -> StftBuilder<f64> {
+    StftBuilder::new()
+        .n_fft(1024)
+        .hop_length(256)
+        .pad_mode(PadMode::Reflect)
+        .normalize(false)
+}
+
+#[test]
+fn test_dct_orthonormal() {
+    let dct = filters::dct::<f64>(32, 32);
+    assert!(dct.dot(&dct.t()).all_close(&Array2::eye(32), 1e-12));
+    // A constant input only has a DC coefficient
+    let c = filters::dct::<f64>(8, 32).dot(&Array1::ones(32));
+    assert!((c[0] - 32f64.sqrt()).abs() < 1e-12);
+    assert!(c.slice(s![1..]).iter().all(|v| v.abs() < 1e-12));
+}
+
+#[test]
+fn test_mel_scale() {
+    assert!((MelScale::Slaney.hz_to_mel(1000.) - 15f64).abs() < 1e-12);
+    assert!((MelScale::Slaney.hz_to_mel(500.) - 7.5f64).abs() < 1e-12);
+    assert!((MelScale::Htk.hz_to_mel(700.) - 2595. * 2f64.log10()).abs() < 1e-9);
+    for &scale in &[MelScale::Htk, MelScale::Slaney] {
+        for &f in &[0f64, 100., 999., 1000., 4321., 22050.] {
+            assert!((scale.mel_to_hz(scale.hz_to_mel(f)) - f).abs() < 1e-9);
+        }
+    }
+}
+
+#[test]
+fn test_mel_filters() {
+    // Many narrow filters still sample their triangles at the exact bin frequencies
+    let fb = filters::mel_with::<f64>(16000, 512, 64, None, None, MelScale::Htk, None).unwrap();
+    assert_eq!(fb.dim(), (257, 64));
+    assert_eq!(filters::mel(16000, 512, 64, None, None).unwrap(), fb);
+    assert!(fb.axis_iter(Axis(1)).all(|filter| filter.scalar_sum() > 0.));
+    assert!(fb.iter().all(|&v| (0. ..=1.).contains(&v)));
+
+    // A filter peaks at 1 when its center falls on a bin
+    let fb = filters::mel_with::<f64>(
+        16000,
+        16000,
+        10,
+        Some(100.),
+        Some(1100.),
+        MelScale::Slaney,
+        None,
+    )
+    .unwrap();
+    for filter in fb.axis_iter(Axis(1)) {
+        assert!((filter.fold(0., |a: f64, &b| a.max(b)) - 1.).abs() < 1e-2);
+    }
+
+    for &norm in &[MelNorm::L1, MelNorm::L2] {
+        let fb =
+            filters::mel_with::<f64>(22050, 2048, 40, None, None, MelScale::Slaney, Some(norm))
+                .unwrap();
+        for filter in fb.axis_iter(Axis(1)) {
+            let measure = match norm {
+                MelNorm::L1 => filter.scalar_sum(),
+                _ => filter.dot(&filter).sqrt(),
+            };
+            assert!((measure - 1.).abs() < 1e-12);
+        }
+    }
+
+    // Slaney normalization gives the triangles a unit area in Hz
+    let fb = filters::mel_with::<f64>(
+        22050,
+        8192,
+        40,
+        None,
+        None,
+        MelScale::Slaney,
+        Some(MelNorm::Slaney),
+    )
+    .unwrap();
+    let df = 22050. / 8192.;
+    assert!(fb
+        .axis_iter(Axis(1))
+        .all(|f| (f.scalar_sum() * df - 1.).abs() < 0.01));
+
+    assert!(filters::mel_with::<f64>(16000, 512, 0, None, None, MelScale::Htk, None).is_err());
+    assert!(filters::mel_with::<f64>(
+        16000,
+        512,
+        40,
+        Some(5000.),
+        Some(4000.),
+        MelScale::Htk,
+        None
+    )
+    .is_err());
+}
+
+#[test]
+fn test_mfcc_shape() {
+    let x = Array1::random(10000, Normal::new(0., 1.));
+    let mfcc = MfccBuilder::new()
+        .stft(_stft().build().unwrap())
+        .n_mfcc(13)
+        .n_mels(40)
+        .build()
+        .unwrap();
+    let n_frames = mfcc.stft().output_shape(x.len()).unwrap().1;
+    assert_eq!(mfcc.process(&x).unwrap().dim(), (13, n_frames));
+    assert_eq!(mfcc.mel_basis.dim(), (513, 40));
+    assert_eq!(mfcc.dct.dim(), (13, 40));
+}
+
+#[test]
+fn test_mfcc_lifter_energy() {
+    let x = Array1::random(10000, Normal::new(0., 1.));
+    let builder = || MfccBuilder::new().stft(_stft().build().unwrap()).n_mfcc(13);
+    let plain = builder().build().unwrap().process(&x).unwrap();
+
+    let lifter = 22.;
+    let liftered = builder()
+        .lifter(lifter)
+        .build()
+        .unwrap()
+        .process(&x)
+        .unwrap();
+    for (k, (row, row_liftered)) in plain.outer_iter().zip(liftered.outer_iter()).enumerate() {
+        let weight = 1. + lifter / 2. * (std::f64::consts::PI * (k + 1) as f64 / lifter).sin();
+        assert!((&row * weight).all_close(&row_liftered, 1e-9));
+    }
+
+    let energy = builder().energy(true).build().unwrap().process(&x).unwrap();
+    // Energy of the windowed frames of the reflect padded signal
+    let window = _stft().build().unwrap().window;
+    let n = x.len() as isize;
+    let padded = Array1::from_shape_fn(x.len() + 1024, |i| {
+        let j = (i as isize - 512).abs();
+        x[if j < n { j } else { 2 * (n - 1) - j } as usize]
+    });
+    let log_energy = Array1::from_shape_fn(energy.shape()[1], |t| {
+        let frame = &padded.slice(s![t * 256..t * 256 + 1024]) * &window;
+        frame.dot(&frame).ln()
+    });
+    assert!(energy.row(0).all_close(&log_energy, 1e-9));
+    assert!(energy
+        .slice(s![1.., ..])
+        .all_close(&plain.slice(s![1.., ..]), 1e-12));
+}
+
+#[test]
+fn test_mfcc_build_errors() {
+    assert!(MfccBuilder::<f64>::new().n_mfcc(0).build().is_err());
+    assert!(MfccBuilder::<f64>::new()
+        .n_mfcc(41)
+        .n_mels(40)
+        .build()
+        .is_err());
+    assert!(MfccBuilder::<f64>::new().lifter(-1.).build().is_err());
+    assert!(MfccBuilder::<f64>::new().top_db(-1.).build().is_err());
+}
@@ -0,0 +1,84 @@
+extern crate audio_featrs;
+#[macro_use]
+extern crate ndarray;
+extern crate ndarray_rand;
+extern crate rand;
+
+use std::f64::consts::PI;
+
+use audio_featrs::{windows, MultitaperBuilder, OutputMode, PadMode, StftBuilder};
+use ndarray::prelude::*;
+use ndarray_rand::RandomExt;
+use rand::distributions::Normal;
+
+#[test]
+fn test_multitaper_single_taper() {
+    let x = Array1::random(4000, Normal::new(0., 1.));
+    let multitaper = MultitaperBuilder::<f64>::new()
+        .n_fft(256)
+        .hop_length(64)
+        .nw(2.5)
+        .k(1)
+        .pad_mode(PadMode::Reflect)
+        .build()
+        .unwrap();
+    let (tapers, _) = windows::dpss(256, 2.5, 1, false);
+    let spec = StftBuilder::new()
+        .n_fft(256)
+        .hop_length(64)
+        .pad_mode(PadMode::Reflect)
+        .window(tapers.row(0).to_owned())
+        .normalize(false)
+        .output_mode(OutputMode::Power(2.))
+        .build()
+        .unwrap()
+        .process_view(&x)
+        .unwrap();
+    assert!(multitaper.process(&x).unwrap().all_close(&spec, 1e-10));
+}
+
+#[test]
+fn test_multitaper_white_noise() {
+    let x = Array1::random(64000, Normal::new(0., 2.));
+    for &adaptive in &[false, true] {
+        let multitaper = MultitaperBuilder::<f64>::new()
+            .n_fft(512)
+            .hop_length(512)
+            .adaptive(adaptive)
+            .build()
+            .unwrap();
+        assert_eq!(multitaper.tapers.shape(), &[7, 512]);
+        let spec = multitaper.process(&x).unwrap();
+        // Unit energy tapers preserve the variance of 4 in every bin
+        let n_bins = spec.rows();
+        let mean = spec.slice(s![1..n_bins - 1, ..]).mean_axis(Axis(1));
+        for &m in mean.iter() {
+            assert!((m - 4.).abs() < 1.2, "{} !~ 4", m);
+        }
+    }
+}
+
+#[test]
+fn test_multitaper_adaptive_leakage() {
+    let noise = Array1::random(16000, Normal::new(0., 1e-4));
+    let x = Array1::from_shape_fn(16000, |i| (2. * PI * 0.1 * i as f64).sin()) + noise;
+    let builder = || {
+        MultitaperBuilder::<f64>::new()
+            .n_fft(512)
+            .hop_length(256)
+            .nw(4.)
+    };
+    let spec = builder().build().unwrap().process(&x).unwrap();
+    let spec_adaptive = builder()
+        .adaptive(true)
+        .build()
+        .unwrap()
+        .process(&x)
+        .unwrap();
+    // Both find the peak, the adaptive weights suppress the leakage of the higher tapers
+    let peak = (0.1 * 512.) as usize;
+    assert!(spec_adaptive[[peak, 5]] > 0.5 * spec[[peak, 5]]);
+    let far = spec.slice(s![150.., ..]).scalar_sum();
+    let far_adaptive = spec_adaptive.slice(s![150.., ..]).scalar_sum();
+    assert!(far_adaptive <= far, "{} > {}", far_adaptive, far);
+}
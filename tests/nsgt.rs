@@ -0,0 +1,112 @@
+extern crate audio_featrs;
+extern crate ndarray;
+extern crate ndarray_rand;
+extern crate rand;
+
+use std::f64::consts::PI;
+
+use audio_featrs::{FrequencyScale, NsgtBuilder, Window};
+use ndarray::prelude::*;
+use ndarray_rand::RandomExt;
+use rand::distributions::Normal;
+
+fn _snr(x: &Array1<f64>, y: &[f64]) -> f64 {
+    let signal: f64 = x.iter().map(|v| v * v).sum();
+    let noise: f64 = x.iter().zip(y).map(|(a, b)| (a - b).powi(2)).sum();
+    10. * (signal / noise).log10()
+}
+
+fn _test_roundtrip(builder: NsgtBuilder<f64>, length: usize) {
+    let nsgt = builder.length(length).build().unwrap();
+    let x = Array1::random(length, Normal::new(0., 1.));
+    let coefs = nsgt.process(&x).unwrap();
+    assert_eq!(coefs.len(), nsgt.n_bands());
+    assert_eq!(
+        coefs.iter().map(|c| c.len()).collect::<Vec<_>>(),
+        nsgt.n_coefficients()
+    );
+    let y = nsgt.inverse(&coefs).unwrap();
+    assert_eq!(y.len(), length);
+    let snr = _snr(&x, &y);
+    assert!(snr > 200., "SNR {} dB", snr);
+}
+
+#[test]
+fn test_nsgt_roundtrip_log() {
+    _test_roundtrip(NsgtBuilder::new(), 22050);
+    _test_roundtrip(NsgtBuilder::new(), 10001);
+}
+
+#[test]
+fn test_nsgt_roundtrip_mel() {
+    _test_roundtrip(
+        NsgtBuilder::new()
+            .scale(FrequencyScale::Mel)
+            .fmin(20.)
+            .fmax(8000.)
+            .n_bins(40),
+        16384,
+    );
+}
+
+#[test]
+fn test_nsgt_roundtrip_bark() {
+    _test_roundtrip(
+        NsgtBuilder::new()
+            .sr(16000.)
+            .scale(FrequencyScale::Bark)
+            .fmin(50.)
+            .fmax(7000.)
+            .n_bins(24)
+            .window(Window::Hamming),
+        12345,
+    );
+}
+
+#[test]
+fn test_nsgt_roundtrip_matrix_form() {
+    let nsgt = NsgtBuilder::<f64>::new()
+        .length(8000)
+        .matrix_form(true)
+        .build()
+        .unwrap();
+    let n_coefs = nsgt.n_coefficients();
+    assert!(n_coefs.iter().all(|&n| n == n_coefs[0]));
+    _test_roundtrip(NsgtBuilder::new().matrix_form(true), 8000);
+}
+
+#[test]
+fn test_nsgt_sine() {
+    let sr = 22050.;
+    let nsgt = NsgtBuilder::<f64>::new().length(22050).build().unwrap();
+    assert_eq!(nsgt.frequencies.len(), 86);
+    assert_eq!(nsgt.frequencies[0], 0.);
+    assert_eq!(nsgt.frequencies[85], sr / 2.);
+    for &k in &[1, 30, 84] {
+        let freq = nsgt.frequencies[k];
+        let x = Array1::from_shape_fn(22050, |i| (2. * PI * freq * i as f64 / sr).sin());
+        let coefs = nsgt.process(&x).unwrap();
+        // Energy of the windowed spectrum of every band
+        let energy: Vec<f64> = coefs
+            .iter()
+            .map(|c| c.iter().map(|v| v.norm_sqr()).sum::<f64>() * c.len() as f64)
+            .collect();
+        let peak = (0..energy.len()).fold(0, |m, i| if energy[i] > energy[m] { i } else { m });
+        assert_eq!(peak, k);
+    }
+}
+
+#[test]
+fn test_nsgt_errors() {
+    assert!(NsgtBuilder::<f64>::new().build().is_err());
+    assert!(NsgtBuilder::<f64>::new()
+        .length(1000)
+        .fmax(20000.)
+        .build()
+        .is_err());
+    let nsgt = NsgtBuilder::<f64>::new().length(1000).build().unwrap();
+    assert!(nsgt.process(&Array1::zeros(999)).is_err());
+    let mut coefs = nsgt.process(&Array1::zeros(1000)).unwrap();
+    coefs.pop();
+    assert!(nsgt.inverse(&coefs).is_err());
+}
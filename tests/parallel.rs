@@ -0,0 +1,54 @@
+#![cfg(feature = "rayon")]
+extern crate audio_featrs;
+extern crate ndarray;
+extern crate ndarray_rand;
+extern crate rand;
+extern crate rayon;
+
+use std::sync::Arc;
+
+use audio_featrs::{PadMode, Parallelism, StftBuilder};
+use ndarray::prelude::*;
+use ndarray_rand::RandomExt;
+use rand::distributions::Normal;
+
+fn _builder(parallelism: Parallelism) -> StftBuilder<f64> {
+    StftBuilder::new()
+        .n_fft(512)
+        .hop_length(100)
+        .pad_mode(PadMode::Center)
+        .parallelism(parallelism)
+}
+
+#[test]
+fn test_parallel_equals_serial() {
+    let x = Array1::random(48000, Normal::new(0., 1.));
+    let pool = Arc::new(
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(3)
+            .build()
+            .unwrap(),
+    );
+    let serial = _builder(Parallelism::Serial).build().unwrap();
+    let spec = serial.process_view(&x).unwrap();
+    for parallelism in &[Parallelism::Global, Parallelism::Pool(pool)] {
+        let stft = _builder(parallelism.clone()).build().unwrap();
+        assert_eq!(stft.process_view(&x).unwrap(), spec);
+    }
+}
+
+#[test]
+fn test_parallel_batch_equals_serial() {
+    let x = Array3::random((2, 3, 20000), Normal::new(0., 1.));
+    let serial = _builder(Parallelism::Serial).build().unwrap();
+    let parallel = _builder(Parallelism::Global).build().unwrap();
+    assert_eq!(
+        parallel.process_batch(&x).unwrap(),
+        serial.process_batch(&x).unwrap()
+    );
+    let x = x.index_axis(Axis(0), 1);
+    assert_eq!(
+        parallel.process_multichannel(x).unwrap(),
+        serial.process_multichannel(x).unwrap()
+    );
+}
@@ -0,0 +1,130 @@
+#[macro_use]
+extern crate ndarray;
+extern crate audio_featrs;
+extern crate ndarray_rand;
+extern crate rand;
+
+use std::f64::consts::PI;
+
+use audio_featrs::{periodogram, welch, Average, Detrend, Scaling, WelchBuilder, Window};
+use ndarray::prelude::*;
+use ndarray_rand::RandomExt;
+use rand::distributions::Normal;
+
+#[test]
+fn test_periodogram_parseval() {
+    let fs = 100.;
+    for &size in &[1000, 999] {
+        let x = Array1::random(size, Normal::new(0., 1.));
+        let x = &x - x.mean_axis(Axis(0)).into_scalar();
+        let (freqs, psd) = periodogram(&x, fs).unwrap();
+        assert_eq!(freqs.len(), size / 2 + 1);
+        assert!((freqs[1] - fs / size as f64).abs() < 1e-12);
+        // The density integrates to the mean power
+        let power = psd.scalar_sum() * fs / size as f64;
+        let power_gt = x.map(|v| v * v).mean_axis(Axis(0)).into_scalar();
+        assert!(
+            (power - power_gt).abs() < 1e-10,
+            "{} !~ {}",
+            power,
+            power_gt
+        );
+    }
+}
+
+#[test]
+fn test_welch_sinusoid_spectrum() {
+    let (fs, f0, amp) = (1000., 125., 3.);
+    let x = Array1::from_shape_fn(10000, |i| amp * (2. * PI * f0 * i as f64 / fs).sin() + 1.5);
+    let welch = WelchBuilder::new()
+        .fs(fs)
+        .nperseg(256)
+        .scaling(Scaling::Spectrum)
+        .build()
+        .unwrap();
+    let psd = welch.process(&x).unwrap();
+    // The power spectrum shows the mean power amp^2 / 2 at f0, the offset is detrended
+    let bin = (f0 / fs * 256.) as usize;
+    assert!((welch.frequencies()[bin] - f0).abs() < 1e-10);
+    assert!((psd[bin] - amp * amp / 2.).abs() < 1e-9, "{}", psd[bin]);
+    assert!(psd[0] < 1e-20);
+}
+
+#[test]
+fn test_welch_detrend_linear() {
+    let x = Array1::from_shape_fn(1024, |i| 0.3 * i as f64 - 2.);
+    let welch = WelchBuilder::new()
+        .nperseg(128)
+        .noverlap(32)
+        .detrend(Detrend::Linear)
+        .build()
+        .unwrap();
+    assert_eq!(welch.n_segments(1024), 10);
+    assert!(welch.process(&x).unwrap().iter().all(|&p| p < 1e-20));
+    let welch = WelchBuilder::new()
+        .nperseg(128)
+        .detrend(Detrend::None)
+        .build()
+        .unwrap();
+    assert!(welch.process(&x).unwrap()[0] > 1.);
+}
+
+#[test]
+fn test_welch_white_noise() {
+    let fs = 8000.;
+    let x = Array1::random(200000, Normal::new(0., 2.));
+    let (_, psd_mean) = welch(&x, fs).unwrap();
+    let psd_median = WelchBuilder::new()
+        .fs(fs)
+        .average(Average::Median)
+        .window_named(Window::Hamming)
+        .build()
+        .unwrap()
+        .process(&x)
+        .unwrap();
+    // One-sided density of white noise is 2 variance / fs
+    let density = 2. * 4. / fs;
+    for psd in &[psd_mean, psd_median] {
+        let mean = psd
+            .slice(s![1..psd.len() - 1])
+            .mean_axis(Axis(0))
+            .into_scalar();
+        assert!(
+            (mean - density).abs() < 0.05 * density,
+            "{} !~ {}",
+            mean,
+            density
+        );
+    }
+}
+
+#[test]
+fn test_welch_twosided() {
+    let x = Array1::random(3000, Normal::new(0., 1.));
+    let builder = || WelchBuilder::new().fs(2.).nperseg(100).nfft(129);
+    let onesided = builder().build().unwrap().process(&x).unwrap();
+    let welch = builder().onesided(false).build().unwrap();
+    let twosided = welch.process(&x).unwrap();
+    assert_eq!(twosided.len(), 129);
+    let freqs = welch.frequencies();
+    assert!((freqs[64] - 64. * 2. / 129.).abs() < 1e-12);
+    assert!((freqs[65] + 64. * 2. / 129.).abs() < 1e-12);
+    assert!((twosided.scalar_sum() - onesided.scalar_sum()).abs() < 1e-10);
+    assert_eq!(twosided[1], twosided[128]);
+}
+
+#[test]
+fn test_welch_errors() {
+    assert!(WelchBuilder::<f64>::new()
+        .nperseg(16)
+        .noverlap(16)
+        .build()
+        .is_err());
+    assert!(WelchBuilder::<f64>::new()
+        .nperseg(16)
+        .nfft(8)
+        .build()
+        .is_err());
+    let welch = WelchBuilder::<f64>::new().nperseg(16).build().unwrap();
+    assert!(welch.process(&vec![0.; 15][..]).is_err());
+}
@@ -0,0 +1,71 @@
+extern crate audio_featrs;
+#[macro_use]
+extern crate ndarray;
+
+use std::f64::consts::PI;
+
+use audio_featrs::{reassigned_spectrogram, PadMode, ReassignmentBuilder, StftBuilder};
+use ndarray::prelude::*;
+
+#[test]
+fn test_reassigned_frequencies() {
+    let (sr, f0) = (8000., 1001.3);
+    let x = Array1::from_shape_fn(4000, |i| (2. * PI * f0 * i as f64 / sr).sin());
+    let stft = StftBuilder::<f64>::new()
+        .n_fft(256)
+        .hop_length(64)
+        .pad_mode(PadMode::Reflect)
+        .build()
+        .unwrap();
+    let spec = reassigned_spectrogram(&stft, &x, sr).unwrap();
+    assert_eq!(spec.freqs.dim(), stft.output_shape(x.len()).unwrap());
+    assert_eq!(spec.times.dim(), spec.mags.dim());
+    assert_eq!(spec.mags, stft.process_view(&x).unwrap());
+    // Bins next to the sinusoid are reassigned to its frequency
+    let bin = (f0 / sr * 256.).round() as usize;
+    let n_frames = spec.freqs.cols();
+    for &f in spec.freqs.slice(s![bin - 1..bin + 2, 4..n_frames - 4]).iter() {
+        assert!((f - f0).abs() < 0.5, "{} !~ {}", f, f0);
+    }
+    assert!(spec
+        .freqs
+        .iter()
+        .all(|&f| f.is_nan() || (f >= 0. && f <= sr / 2.)));
+}
+
+#[test]
+fn test_reassigned_times() {
+    let sr = 1000.;
+    let mut x = Array1::zeros(2000);
+    x[1234] = 1.;
+    let stft = StftBuilder::<f64>::new()
+        .n_fft(128)
+        .hop_length(32)
+        .build()
+        .unwrap();
+    let reassignment = ReassignmentBuilder::new()
+        .ref_power(Some(1e-8))
+        .build()
+        .unwrap();
+    let spec = reassignment.process(&stft, &x, sr).unwrap();
+    // Bins of frames containing the impulse are reassigned to its time, the others are NaN.
+    // As in librosa, the time weights of even windows are offset by half a sample.
+    let mut n_reassigned = 0;
+    for &t in spec.times.iter().filter(|t| !t.is_nan()) {
+        assert!((t - 1.2345).abs() < 1e-6, "{} !~ 1.2345", t);
+        n_reassigned += 1;
+    }
+    assert!(n_reassigned > 0);
+
+    let spec = ReassignmentBuilder::new()
+        .fill_nan(true)
+        .reassign_frequencies(false)
+        .build()
+        .unwrap()
+        .process(&stft, &x, sr)
+        .unwrap();
+    assert!(spec.times.iter().all(|t| !t.is_nan()));
+    // Frame times are the centers of the frames
+    assert!((spec.times[[0, 0]] - 0.064).abs() < 1e-12);
+    assert!((spec.freqs[[10, 3]] - 10. * sr / 128.).abs() < 1e-12);
+}
@@ -1,3 +1,4 @@
+#[macro_use]
 extern crate ndarray;
 extern crate ndarray_rand;
 extern crate num_traits;
@@ -8,14 +9,22 @@ extern crate rand;
 extern crate audio_featrs;
 
 use ndarray::prelude::*;
-use ndarray_rand::RandomExt;
+use ndarray::Zip;
+use ndarray_rand::{RandomExt, F32};
 use num_traits::Float;
-use numpy::{IntoPyArray, PyArrayDyn, TypeNum};
-use pyo3::{prelude::*, types::PyDict, PyResult};
+use numpy::npyffi::NPY_ORDER;
+use numpy::{Element, PyArray1, PyArrayDyn, PyArrayMethods};
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyDict, PyResult};
 use rand::distributions::Normal;
+use std::ffi::CString;
 use std::fmt::Debug;
 
-use audio_featrs::{PadMode, StftBuilder};
+use audio_featrs::filters::{self, MelNorm, MelScale, Norm};
+use audio_featrs::{
+    features, Average, CqtBuilder, Detrend, MfccBuilder, MultitaperBuilder, OutputMode, PadMode,
+    ReassignmentBuilder, Scaling, StftBuilder, WelchBuilder, Window,
+};
+use audio_featrs::{kaldi, whisper};
 
 type Result<T> = ::std::result::Result<T, Box<::std::error::Error>>;
 
@@ -45,7 +54,35 @@ where
     assert!(is_close, "assert_close() failed!");
 }
 
-fn _librosa_stft<T: Float + TypeNum>(
+/// Evaluates the python expression `code`.
+fn _eval<'py>(
+    py: Python<'py>,
+    code: &str,
+    globals: &Bound<'py, PyDict>,
+    locals: Option<&Bound<'py, PyDict>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let code = CString::new(code).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    py.eval(&code, Some(globals), locals)
+}
+
+/// Copies `x` into a numpy array of the same shape.
+///
+/// numpy links another version of ndarray, so the data is passed as a vector in Fortran order.
+fn _to_numpy<'py, T, D>(py: Python<'py>, x: &Array<T, D>) -> PyResult<Bound<'py, PyArrayDyn<T>>>
+where
+    T: Element + Copy,
+    D: Dimension,
+{
+    let data = x.t().iter().cloned().collect();
+    PyArray1::from_vec(py, data).reshape_with_order(x.shape().to_vec(), NPY_ORDER::NPY_FORTRANORDER)
+}
+
+/// Copies the numpy array `a` in memory order.
+fn _to_vec<T: Element + Copy>(a: &Bound<PyAny>) -> PyResult<Vec<T>> {
+    Ok(a.cast::<PyArrayDyn<T>>()?.readonly().as_slice()?.to_vec())
+}
+
+fn _librosa_stft<T: Float + Element>(
     py: Python,
     x: Array1<T>,
     n_fft: usize,
@@ -58,24 +95,85 @@ fn _librosa_stft<T: Float + TypeNum>(
     globals.set_item("np", py.import("numpy")?)?;
 
     let locals = PyDict::new(py);
-    locals.set_item("x", x.into_pyarray(py))?;
+    locals.set_item("x", _to_numpy(py, &x)?)?;
     locals.set_item("n_fft", n_fft)?;
     locals.set_item("hop_length", hop_length)?;
     locals.set_item("win_length", win_length)?;
     match window {
-        Some(w) => locals.set_item("window", w.into_pyarray(py))?,
+        Some(w) => locals.set_item("window", _to_numpy(py, &w)?)?,
         None => locals.set_item("window", "hann")?,
     };
-    let spec: &PyArrayDyn<T> = py
-        .eval(
-            "np.abs(librosa.stft(x, n_fft, hop_length, win_length, window, center=False),
+    let spec = _eval(
+        py,
+        "np.abs(librosa.stft(x, n_fft=n_fft, hop_length=hop_length, win_length=win_length,
+                                 window=window, center=False),
                     dtype=x.dtype)",
-            Some(&globals),
-            Some(&locals),
-        )?
-        .extract()?;
+        &globals,
+        Some(&locals),
+    )?;
+
+    _to_vec(&spec)
+}
+
+fn _librosa_stft_complex<T: Float + Element>(
+    py: Python,
+    x: Array1<T>,
+    n_fft: usize,
+    hop_length: usize,
+) -> PyResult<(Vec<T>, Vec<T>)> {
+    let globals = PyDict::new(py);
+    globals.set_item("librosa", py.import("librosa")?)?;
+    globals.set_item("np", py.import("numpy")?)?;
+
+    let locals = PyDict::new(py);
+    locals.set_item("x", _to_numpy(py, &x)?)?;
+    locals.set_item("n_fft", n_fft)?;
+    locals.set_item("hop_length", hop_length)?;
+    let re = _eval(
+        py,
+        "np.asfortranarray(np.real(librosa.stft(x, n_fft=n_fft, hop_length=hop_length,
+                                                   center=False)),
+                                 dtype=x.dtype)",
+        &globals,
+        Some(&locals),
+    )?;
+    let im = _eval(
+        py,
+        "np.asfortranarray(np.imag(librosa.stft(x, n_fft=n_fft, hop_length=hop_length,
+                                                   center=False)),
+                                 dtype=x.dtype)",
+        &globals,
+        Some(&locals),
+    )?;
 
-    Ok(spec.as_slice().to_vec())
+    Ok((_to_vec(&re)?, _to_vec(&im)?))
+}
+
+fn _librosa_stft_center<T: Float + Element>(
+    py: Python,
+    x: Array1<T>,
+    n_fft: usize,
+    hop_length: usize,
+    pad_mode: &str,
+) -> PyResult<(Vec<usize>, Vec<T>)> {
+    let globals = PyDict::new(py);
+    globals.set_item("librosa", py.import("librosa")?)?;
+    globals.set_item("np", py.import("numpy")?)?;
+
+    let locals = PyDict::new(py);
+    locals.set_item("x", _to_numpy(py, &x)?)?;
+    locals.set_item("n_fft", n_fft)?;
+    locals.set_item("hop_length", hop_length)?;
+    locals.set_item("pad_mode", pad_mode)?;
+    let spec = _eval(
+        py,
+        "np.asfortranarray(np.abs(librosa.stft(x, n_fft=n_fft, hop_length=hop_length, center=True,
+                                                   pad_mode=pad_mode)), dtype=x.dtype)",
+        &globals,
+        Some(&locals),
+    )?;
+
+    Ok((spec.getattr("shape")?.extract()?, _to_vec(&spec)?))
 }
 
 fn _test_stft(
@@ -84,7 +182,7 @@ fn _test_stft(
     hop_length: Option<usize>,
     win_length: Option<usize>,
     rand_win_size: Option<usize>,
-    pad: PadMode,
+    pad: PadMode<f64>,
 ) -> Result<()> {
     let x = Array1::random(size, Normal::new(-1., 1.));
     let builder = StftBuilder::<f64>::new()
@@ -118,17 +216,16 @@ fn _test_stft(
     println!("spec shape: {:?}", spec.shape());
 
     let win_length = win_length.unwrap_or(n_fft);
-    let gil = Python::acquire_gil();
-    let py = gil.python();
-    let spec_gt = _librosa_stft(py, x, n_fft, stft.hop_length, win_length, window)
-        .map_err(|e| {
+    let spec_gt = Python::attach(|py| {
+        _librosa_stft(py, x, n_fft, stft.hop_length, win_length, window).map_err(|e| {
             eprintln!("Error calling _librosa_stft(): {:?}", e);
             eprintln!(
                 "Consider using `RUST_TEST_THREADS=1 cargo test` for a correct printing order."
             );
-            e.print_and_set_sys_last_vars(gil.python()); // Print error manually
+            e.print_and_set_sys_last_vars(py); // Print error manually
         })
-        .unwrap();
+    })
+    .unwrap();
 
     // TODO: Investigate low precision of the 0th frequency bin in comparison with librosa
     assert_close(spec.as_slice_memory_order().unwrap(), &spec_gt, 7e-5.into());
@@ -189,5 +286,1137 @@ fn test_stft_build_assertion_4() {
     _test_stft(10, 11, None, None, None, PadMode::Truncate).unwrap();
 }
 
-// TODO: test_stft_pad_center()
-// Center is not supported atm because we need a reflect pad mode as provided by numpy
+fn _test_stft_complex(size: usize, n_fft: usize, hop_length: usize) -> Result<()> {
+    let x = Array1::random(size, Normal::new(-1., 1.));
+    let stft = StftBuilder::<f64>::new()
+        .n_fft(n_fft)
+        .hop_length(hop_length)
+        .normalize(false)
+        .build()?;
+    let spec = stft.process_complex(x.as_slice().unwrap().to_vec())?;
+
+    let (re_gt, im_gt) = Python::attach(|py| {
+        _librosa_stft_complex(py, x, n_fft, hop_length).map_err(|e| {
+            eprintln!("Error calling _librosa_stft_complex(): {:?}", e);
+            e.print_and_set_sys_last_vars(py);
+        })
+    })
+    .unwrap();
+
+    let re = spec.map(|v| v.re);
+    let im = spec.map(|v| v.im);
+    assert_close(re.as_slice_memory_order().unwrap(), &re_gt, 7e-5.into());
+    assert_close(im.as_slice_memory_order().unwrap(), &im_gt, 7e-5.into());
+    Ok(())
+}
+
+#[test]
+fn test_stft_complex_basic() {
+    _test_stft_complex(10, 10, 5).unwrap();
+}
+#[test]
+fn test_stft_complex_hop() {
+    _test_stft_complex(4000, 1024, 441).unwrap();
+}
+
+fn _test_stft_center(
+    size: usize,
+    n_fft: usize,
+    hop_length: usize,
+    pad: PadMode<f64>,
+) -> Result<()> {
+    let x = Array1::random(size, Normal::new(0., 1.));
+    let stft = StftBuilder::<f64>::new()
+        .n_fft(n_fft)
+        .hop_length(hop_length)
+        .normalize(false)
+        .pad_mode(pad)
+        .build()?;
+    let spec = stft.process_view(&x)?;
+    let pad_mode = match pad {
+        PadMode::Reflect => "reflect",
+        PadMode::Edge => "edge",
+        PadMode::Constant(_) => "constant",
+        _ => return Err(From::from("librosa only supports centered padding.")),
+    };
+
+    let (shape_gt, spec_gt) = Python::attach(|py| {
+        _librosa_stft_center(py, x, n_fft, hop_length, pad_mode).map_err(|e| {
+            eprintln!("Error calling _librosa_stft_center(): {:?}", e);
+            e.print_and_set_sys_last_vars(py);
+        })
+    })
+    .unwrap();
+
+    assert_eq!(spec.shape(), &shape_gt[..]);
+    assert_close(spec.as_slice_memory_order().unwrap(), &spec_gt, 7e-5.into());
+    Ok(())
+}
+
+#[test]
+fn test_stft_pad_reflect() {
+    _test_stft_center(4000, 1024, 256, PadMode::Reflect).unwrap();
+    _test_stft_center(4000, 1024, 441, PadMode::Reflect).unwrap();
+    _test_stft_center(700, 1024, 128, PadMode::Reflect).unwrap();
+}
+#[test]
+fn test_stft_pad_edge() {
+    _test_stft_center(4000, 1024, 441, PadMode::Edge).unwrap();
+    _test_stft_center(300, 512, 100, PadMode::Edge).unwrap();
+}
+#[test]
+fn test_stft_pad_constant() {
+    _test_stft_center(4000, 1024, 441, PadMode::Constant(0.)).unwrap();
+    _test_stft_center(300, 512, 100, PadMode::Constant(0.)).unwrap();
+}
+
+fn _librosa_reassigned<T: Float + Element>(
+    py: Python,
+    x: Array1<T>,
+    sr: usize,
+    n_fft: usize,
+    hop_length: usize,
+) -> PyResult<Vec<Vec<T>>> {
+    let globals = PyDict::new(py);
+    globals.set_item("librosa", py.import("librosa")?)?;
+    globals.set_item("np", py.import("numpy")?)?;
+
+    let locals = PyDict::new(py);
+    locals.set_item("x", _to_numpy(py, &x)?)?;
+    locals.set_item("sr", sr)?;
+    locals.set_item("n_fft", n_fft)?;
+    locals.set_item("hop_length", hop_length)?;
+    let spec = _eval(
+        py,
+        "[np.asfortranarray(s, dtype=x.dtype) for s in librosa.reassigned_spectrogram(
+                 x, sr=sr, n_fft=n_fft, hop_length=hop_length, pad_mode='reflect',
+                 fill_nan=True)]",
+        &globals,
+        Some(&locals),
+    )?;
+
+    spec.try_iter()?.map(|s| _to_vec(&s?)).collect()
+}
+
+#[test]
+fn test_reassigned_spectrogram() {
+    let sr = 22050;
+    let x = Array1::random(8000, Normal::new(0., 1.));
+    let stft = StftBuilder::<f64>::new()
+        .n_fft(512)
+        .hop_length(128)
+        .normalize(false)
+        .pad_mode(PadMode::Reflect)
+        .build()
+        .unwrap();
+    let spec = ReassignmentBuilder::new()
+        .fill_nan(true)
+        .build()
+        .unwrap()
+        .process(&stft, &x, sr as f64)
+        .unwrap();
+
+    let spec_gt = Python::attach(|py| {
+        _librosa_reassigned(py, x, sr, 512, 128).map_err(|e| {
+            eprintln!("Error calling _librosa_reassigned(): {:?}", e);
+            e.print_and_set_sys_last_vars(py);
+        })
+    })
+    .unwrap();
+
+    assert_close(
+        spec.freqs.as_slice_memory_order().unwrap(),
+        &spec_gt[0],
+        1e-6,
+    );
+    assert_close(
+        spec.times.as_slice_memory_order().unwrap(),
+        &spec_gt[1],
+        1e-9,
+    );
+    assert_close(
+        spec.mags.as_slice_memory_order().unwrap(),
+        &spec_gt[2],
+        7e-5,
+    );
+}
+
+fn _librosa_cqt<T: Float + Element>(
+    py: Python,
+    x: Array1<T>,
+    func: &str,
+    kwargs: &str,
+) -> PyResult<Vec<T>> {
+    let globals = PyDict::new(py);
+    globals.set_item("librosa", py.import("librosa")?)?;
+    globals.set_item("np", py.import("numpy")?)?;
+
+    let locals = PyDict::new(py);
+    locals.set_item("x", _to_numpy(py, &x)?)?;
+    // The octaves are padded with reflection and downsampled with the kaiser_fast filter of
+    // resampy, which are not the defaults since librosa 0.10
+    let spec = _eval(
+        py,
+        &format!(
+            "np.asfortranarray(np.abs(librosa.{}(x, {}, pad_mode='reflect', \
+             res_type='kaiser_fast')), dtype=x.dtype)",
+            func, kwargs
+        ),
+        &globals,
+        Some(&locals),
+    )?;
+
+    _to_vec(&spec)
+}
+
+fn _test_cqt(builder: CqtBuilder<f64>, size: usize, func: &str, kwargs: &str) {
+    let x = Array1::random(size, Normal::new(0., 1.));
+    let spec = builder.build().unwrap().process(&x).unwrap();
+
+    let spec_gt = Python::attach(|py| {
+        _librosa_cqt(py, x, func, kwargs).map_err(|e| {
+            eprintln!("Error calling _librosa_cqt(): {:?}", e);
+            e.print_and_set_sys_last_vars(py);
+        })
+    })
+    .unwrap();
+
+    assert_close(spec.as_slice_memory_order().unwrap(), &spec_gt, 1e-6);
+}
+
+#[test]
+fn test_cqt_librosa() {
+    _test_cqt(CqtBuilder::new(), 22050, "cqt", "sr=22050");
+    _test_cqt(
+        CqtBuilder::new().sr(44100.).hop_length(256),
+        30000,
+        "cqt",
+        "sr=44100, hop_length=256",
+    );
+    // Highest octave close to Nyquist, computed before any downsampling
+    _test_cqt(
+        CqtBuilder::new().fmin(330.).n_bins(60),
+        20000,
+        "cqt",
+        "sr=22050, fmin=330., n_bins=60",
+    );
+    _test_cqt(
+        CqtBuilder::new()
+            .fmin(55.)
+            .n_bins(50)
+            .bins_per_octave(24)
+            .filter_scale(0.5)
+            .sparsity(0.),
+        10000,
+        "cqt",
+        "sr=22050, fmin=55., n_bins=50, bins_per_octave=24, filter_scale=0.5, sparsity=0.",
+    );
+}
+
+#[test]
+fn test_vqt_librosa() {
+    _test_cqt(
+        CqtBuilder::new().gamma(10.),
+        22050,
+        "vqt",
+        "sr=22050, gamma=10.",
+    );
+    _test_cqt(
+        CqtBuilder::new().sr(44100.).hop_length(256).gamma(3.),
+        30000,
+        "vqt",
+        "sr=44100, hop_length=256, gamma=3.",
+    );
+}
+
+#[test]
+fn test_hybrid_cqt_librosa() {
+    _test_cqt(
+        CqtBuilder::new().hybrid(true),
+        22050,
+        "hybrid_cqt",
+        "sr=22050",
+    );
+    _test_cqt(
+        CqtBuilder::new()
+            .fmin(110.)
+            .n_bins(60)
+            .hop_length(128)
+            .hybrid(true),
+        10000,
+        "hybrid_cqt",
+        "sr=22050, fmin=110., n_bins=60, hop_length=128",
+    );
+}
+
+fn _librosa_chroma<T: Float + Element>(
+    py: Python,
+    spec: Array2<T>,
+    sr: usize,
+    n_chroma: usize,
+    tuning: f64,
+) -> PyResult<Vec<Vec<T>>> {
+    let globals = PyDict::new(py);
+    globals.set_item("librosa", py.import("librosa")?)?;
+    globals.set_item("np", py.import("numpy")?)?;
+    globals.set_item("mock", py.import("unittest.mock")?)?;
+
+    let locals = PyDict::new(py);
+    locals.set_item("S", _to_numpy(py, &spec)?)?;
+    locals.set_item("sr", sr)?;
+    locals.set_item("n_chroma", n_chroma)?;
+    locals.set_item("tuning", tuning)?;
+    // The filter bank is transposed to compare it in row-major order.
+    // chroma_cens only accepts a CQT, so its chroma_cqt() is replaced by chroma_stft()
+    let chroma = _eval(
+        py,
+        "[np.asfortranarray(c, dtype=S.dtype) for c in (
+                 librosa.filters.chroma(
+                     sr=sr,
+                     n_fft=2 * (S.shape[0] - 1),
+                     n_chroma=n_chroma,
+                     A440=440. * 2**(tuning / 12),
+                 ).T,
+                 librosa.feature.chroma_stft(S=S, sr=sr, n_chroma=n_chroma, tuning=tuning),
+                 mock.patch(
+                     'librosa.feature.spectral.chroma_cqt',
+                     return_value=librosa.feature.chroma_stft(
+                         S=S, sr=sr, n_chroma=n_chroma, tuning=tuning, norm=None
+                     ),
+                 )(lambda: librosa.feature.chroma_cens(sr=sr))(),
+                 librosa.feature.tonnetz(
+                     chroma=librosa.feature.chroma_stft(
+                         S=S, sr=sr, n_chroma=n_chroma, tuning=tuning
+                     ),
+                 ))]",
+        &globals,
+        Some(&locals),
+    )?;
+
+    chroma.try_iter()?.map(|c| _to_vec(&c?)).collect()
+}
+
+#[test]
+fn test_chroma_librosa() {
+    let sr = 22050;
+    let x = Array1::random(20000, Normal::new(0., 1.));
+    let spec = StftBuilder::<f64>::new()
+        .n_fft(2048)
+        .hop_length(512)
+        .pad_mode(PadMode::Reflect)
+        .output_mode(OutputMode::Power(2.))
+        .normalize(false)
+        .build()
+        .unwrap()
+        .process_view(&x)
+        .unwrap();
+    // 24 pitch classes start at C like 12, after a roll by 6 quarter tones
+    for &(n_chroma, tuning) in &[(12, 0.), (12, -0.3), (24, 0.)] {
+        let fb = filters::chroma(
+            sr as f64,
+            2048,
+            n_chroma,
+            tuning,
+            5.,
+            Some(2.),
+            Some(Norm::L2),
+        )
+        .unwrap();
+        let chroma =
+            features::chroma_stft(&spec, sr as f64, 2048, n_chroma, tuning, Some(Norm::Max))
+                .unwrap();
+        let cens = features::chroma_cens(
+            &spec,
+            sr as f64,
+            2048,
+            n_chroma,
+            tuning,
+            Some(41),
+            Some(Norm::L2),
+        )
+        .unwrap();
+        let tonnetz = features::tonnetz(&spec, sr as f64, 2048, n_chroma, tuning).unwrap();
+
+        let chroma_gt = Python::attach(|py| {
+            _librosa_chroma(py, spec.clone(), sr, n_chroma, tuning).map_err(|e| {
+                eprintln!("Error calling _librosa_chroma(): {:?}", e);
+                e.print_and_set_sys_last_vars(py);
+            })
+        })
+        .unwrap();
+
+        assert_close(fb.as_slice_memory_order().unwrap(), &chroma_gt[0], 1e-12);
+        assert_close(chroma.as_slice_memory_order().unwrap(), &chroma_gt[1], 1e-9);
+        assert_close(cens.as_slice_memory_order().unwrap(), &chroma_gt[2], 1e-9);
+        assert_close(
+            tonnetz.as_slice_memory_order().unwrap(),
+            &chroma_gt[3],
+            1e-9,
+        );
+    }
+}
+
+fn _librosa_mel<T: Float + Element>(py: Python, kwargs: &str) -> PyResult<Vec<T>> {
+    let globals = PyDict::new(py);
+    globals.set_item("librosa", py.import("librosa")?)?;
+    globals.set_item("np", py.import("numpy")?)?;
+
+    let fb = _eval(
+        py,
+        &format!(
+            "np.asfortranarray(librosa.filters.mel({}, dtype=np.float64))",
+            kwargs
+        ),
+        &globals,
+        None,
+    )?;
+    _to_vec(&fb)
+}
+
+fn _test_mel(fb: Array2<f64>, kwargs: &str) {
+    let fb_gt = Python::attach(|py| {
+        _librosa_mel(py, kwargs).map_err(|e| {
+            eprintln!("Error calling _librosa_mel(): {:?}", e);
+            e.print_and_set_sys_last_vars(py);
+        })
+    })
+    .unwrap();
+
+    assert_close(fb.as_slice_memory_order().unwrap(), &fb_gt, 1e-10);
+}
+
+#[test]
+fn test_mel_librosa() {
+    let norm = Some(MelNorm::Slaney);
+    let fb = filters::mel_with(22050, 2048, 128, None, None, MelScale::Slaney, norm);
+    _test_mel(fb.unwrap(), "sr=22050, n_fft=2048, n_mels=128");
+
+    let fb = filters::mel_with(16000, 512, 40, Some(133.), Some(6855.), MelScale::Htk, None);
+    _test_mel(
+        fb.unwrap(),
+        "sr=16000, n_fft=512, n_mels=40, fmin=133., fmax=6855., htk=True, norm=None",
+    );
+
+    let norm = Some(MelNorm::L1);
+    let fb = filters::mel_with(44100, 1024, 64, Some(20.), None, MelScale::Slaney, norm);
+    _test_mel(
+        fb.unwrap(),
+        "sr=44100, n_fft=1024, n_mels=64, fmin=20., norm=1",
+    );
+
+    let norm = Some(MelNorm::L2);
+    let fb = filters::mel_with(8000, 256, 20, None, Some(3000.), MelScale::Htk, norm);
+    _test_mel(
+        fb.unwrap(),
+        "sr=8000, n_fft=256, n_mels=20, fmax=3000., htk=True, norm=2",
+    );
+}
+
+fn _librosa_mfcc<T: Float + Element>(
+    py: Python,
+    x: Array1<T>,
+    sr: usize,
+    n_mfcc: usize,
+    lifter: f64,
+) -> PyResult<Vec<T>> {
+    let globals = PyDict::new(py);
+    globals.set_item("librosa", py.import("librosa")?)?;
+    globals.set_item("np", py.import("numpy")?)?;
+
+    let locals = PyDict::new(py);
+    locals.set_item("x", _to_numpy(py, &x)?)?;
+    locals.set_item("sr", sr)?;
+    locals.set_item("n_mfcc", n_mfcc)?;
+    locals.set_item("lifter", lifter)?;
+    let mfcc = _eval(
+        py,
+        "np.asfortranarray(librosa.feature.mfcc(
+                 y=x,
+                 sr=sr,
+                 n_mfcc=n_mfcc,
+                 lifter=lifter,
+                 n_fft=2048,
+                 hop_length=512,
+                 center=True,
+                 pad_mode='constant',
+                 dtype=np.float64,
+             ), dtype=x.dtype)",
+        &globals,
+        Some(&locals),
+    )?;
+    _to_vec(&mfcc)
+}
+
+#[test]
+fn test_mfcc_librosa() {
+    let x = Array1::random(20000, Normal::new(0., 1.));
+    for &(n_mfcc, lifter) in &[(20, 0.), (13, 22.)] {
+        let stft = StftBuilder::<f64>::new()
+            .n_fft(2048)
+            .hop_length(512)
+            .pad_mode(PadMode::Constant(0.))
+            .normalize(false)
+            .build()
+            .unwrap();
+        let coefficients = MfccBuilder::new()
+            .stft(stft)
+            .sr(22050)
+            .n_mfcc(n_mfcc)
+            .mel_scale(MelScale::Slaney)
+            .mel_norm(Some(MelNorm::Slaney))
+            .lifter(lifter)
+            .build()
+            .unwrap()
+            .process(&x)
+            .unwrap();
+
+        let mfcc_gt = Python::attach(|py| {
+            _librosa_mfcc(py, x.clone(), 22050, n_mfcc, lifter).map_err(|e| {
+                eprintln!("Error calling _librosa_mfcc(): {:?}", e);
+                e.print_and_set_sys_last_vars(py);
+            })
+        })
+        .unwrap();
+
+        assert_close(
+            coefficients.as_slice_memory_order().unwrap(),
+            &mfcc_gt,
+            1e-6,
+        );
+    }
+}
+
+fn _librosa_mel_to_stft<T: Float + Element>(
+    py: Python,
+    mel: Array2<T>,
+    kwargs: &str,
+) -> PyResult<Vec<T>> {
+    let globals = PyDict::new(py);
+    globals.set_item("librosa", py.import("librosa")?)?;
+    globals.set_item("np", py.import("numpy")?)?;
+
+    let locals = PyDict::new(py);
+    locals.set_item("M", _to_numpy(py, &mel)?)?;
+    let stft = _eval(
+        py,
+        &format!(
+            "np.asfortranarray(librosa.feature.inverse.mel_to_stft(M, {}), dtype=M.dtype)",
+            kwargs
+        ),
+        &globals,
+        Some(&locals),
+    )?;
+    _to_vec(&stft)
+}
+
+#[test]
+fn test_mel_to_stft_librosa() {
+    let x = Array1::random(8000, Normal::new(0., 1.));
+    let power = StftBuilder::<f64>::new()
+        .n_fft(512)
+        .hop_length(128)
+        .pad_mode(PadMode::Reflect)
+        .normalize(false)
+        .output_mode(OutputMode::Power(2.))
+        .build()
+        .unwrap()
+        .process_view(&x)
+        .unwrap();
+    let basis = filters::mel_with(
+        16000,
+        512,
+        64,
+        None,
+        None,
+        MelScale::Slaney,
+        Some(MelNorm::Slaney),
+    )
+    .unwrap();
+    let mel = basis.t().dot(&power);
+    let stft = audio_featrs::mel_to_stft(&mel, &basis, 2.).unwrap();
+
+    let stft_gt = Python::attach(|py| {
+        _librosa_mel_to_stft(py, mel.clone(), "sr=16000, n_fft=512, power=2.").map_err(|e| {
+            eprintln!("Error calling _librosa_mel_to_stft(): {:?}", e);
+            e.print_and_set_sys_last_vars(py);
+        })
+    })
+    .unwrap();
+    let stft_gt = Array2::from_shape_vec(stft.dim().f(), stft_gt).unwrap();
+
+    // The solutions are not unique, but both must explain the mel spectrogram as well
+    let residual = |s: &Array2<f64>| {
+        let reprojected = basis.t().dot(&s.mapv(|v| v * v));
+        (&reprojected - &mel).mapv(|v| v * v).scalar_sum().sqrt()
+    };
+    let norm = mel.mapv(|v| v * v).scalar_sum().sqrt();
+    assert!(residual(&stft) <= residual(&stft_gt) + 1e-3 * norm);
+}
+
+fn _torchaudio_kaldi<T: Float + Element>(
+    py: Python,
+    x: Array1<T>,
+    func: &str,
+    kwargs: &str,
+) -> PyResult<Vec<T>> {
+    let globals = PyDict::new(py);
+    globals.set_item("torch", py.import("torch")?)?;
+    globals.set_item("kaldi", py.import("torchaudio.compliance.kaldi")?)?;
+
+    let locals = PyDict::new(py);
+    locals.set_item("x", _to_numpy(py, &x)?)?;
+    // The output of shape [n_frames, n_features] is the memory order of ours
+    let features = _eval(
+        py,
+        &format!(
+            "kaldi.{}(
+                     torch.from_numpy(x).unsqueeze(0), dither=0., energy_floor=0., {}
+                 ).numpy().ravel()",
+            func, kwargs
+        ),
+        &globals,
+        Some(&locals),
+    )?;
+
+    _to_vec(&features)
+}
+
+fn _test_kaldi<F>(func: &str, kwargs: &str, features: F)
+where
+    F: Fn(&Array1<f64>) -> Array2<f64>,
+{
+    let x = Array1::random(16000, Normal::new(0., 1.));
+    let out = features(&x);
+
+    let out_gt = Python::attach(|py| {
+        _torchaudio_kaldi(py, x, func, kwargs).map_err(|e| {
+            eprintln!("Error calling _torchaudio_kaldi(): {:?}", e);
+            e.print_and_set_sys_last_vars(py);
+        })
+    })
+    .unwrap();
+
+    assert_close(out.as_slice_memory_order().unwrap(), &out_gt, 1e-8);
+}
+
+#[test]
+fn test_kaldi_torchaudio() {
+    let frame_opts = kaldi::FrameExtractionOptions {
+        dither: 0.,
+        ..Default::default()
+    };
+    _test_kaldi("spectrogram", "", |x| {
+        let opts = kaldi::SpectrogramOptions {
+            frame_opts: frame_opts.clone(),
+            ..Default::default()
+        };
+        kaldi::spectrogram(x, &opts).unwrap()
+    });
+    _test_kaldi("fbank", "", |x| {
+        let opts = kaldi::FbankOptions {
+            frame_opts: frame_opts.clone(),
+            ..Default::default()
+        };
+        kaldi::fbank(x, &opts).unwrap()
+    });
+    _test_kaldi(
+        "fbank",
+        "num_mel_bins=40, low_freq=60., high_freq=-400., use_energy=True, raw_energy=False, \
+         snip_edges=False, window_type='hamming'",
+        |x| {
+            let opts = kaldi::FbankOptions {
+                frame_opts: kaldi::FrameExtractionOptions {
+                    snip_edges: false,
+                    window_type: kaldi::WindowType::Hamming,
+                    ..frame_opts.clone()
+                },
+                mel_opts: kaldi::MelBanksOptions {
+                    num_bins: 40,
+                    low_freq: 60.,
+                    high_freq: -400.,
+                },
+                use_energy: true,
+                raw_energy: false,
+                ..Default::default()
+            };
+            kaldi::fbank(x, &opts).unwrap()
+        },
+    );
+    _test_kaldi("mfcc", "use_energy=True", |x| {
+        let opts = kaldi::MfccOptions {
+            frame_opts: frame_opts.clone(),
+            ..Default::default()
+        };
+        kaldi::mfcc(x, &opts).unwrap()
+    });
+    _test_kaldi(
+        "mfcc",
+        "num_ceps=20, num_mel_bins=30, cepstral_lifter=0., htk_compat=True, \
+         preemphasis_coefficient=0., remove_dc_offset=False, window_type='blackman', \
+         round_to_power_of_two=False",
+        |x| {
+            let opts = kaldi::MfccOptions {
+                frame_opts: kaldi::FrameExtractionOptions {
+                    preemph_coeff: 0.,
+                    remove_dc_offset: false,
+                    window_type: kaldi::WindowType::Blackman,
+                    round_to_power_of_two: false,
+                    ..frame_opts.clone()
+                },
+                mel_opts: kaldi::MelBanksOptions {
+                    num_bins: 30,
+                    ..Default::default()
+                },
+                num_ceps: 20,
+                use_energy: false,
+                cepstral_lifter: 0.,
+                htk_compat: true,
+                ..Default::default()
+            };
+            kaldi::mfcc(x, &opts).unwrap()
+        },
+    );
+}
+
+fn _whisper_log_mel<T: Float + Element>(
+    py: Python,
+    x: Array1<T>,
+    n_mels: usize,
+) -> PyResult<Vec<Vec<T>>> {
+    let globals = PyDict::new(py);
+    globals.set_item("torch", py.import("torch")?)?;
+    globals.set_item("librosa", py.import("librosa")?)?;
+    globals.set_item("np", py.import("numpy")?)?;
+    globals.set_item("audio", py.import("whisper.audio")?)?;
+
+    let locals = PyDict::new(py);
+    locals.set_item("x", _to_numpy(py, &x)?)?;
+    locals.set_item("n_mels", n_mels)?;
+    let outputs = _eval(
+        py,
+        "[np.asfortranarray(v, dtype=x.dtype) for v in (
+                 librosa.filters.mel(sr=16000, n_fft=400, n_mels=n_mels, dtype=np.float64),
+                 audio.log_mel_spectrogram(
+                     audio.pad_or_trim(torch.from_numpy(x)), n_mels=n_mels
+                 ).numpy(),
+             )]",
+        &globals,
+        Some(&locals),
+    )?;
+
+    outputs.try_iter()?.map(|v| _to_vec(&v?)).collect()
+}
+
+#[test]
+fn test_whisper_reference() {
+    for &n_mels in &[80, 128] {
+        // Whisper computes in single precision
+        let x = Array1::random(100_000, F32(Normal::new(0., 0.1)));
+        let filters = whisper::mel_filters::<f32>(n_mels).unwrap();
+        let log_spec = whisper::log_mel_spectrogram(&x, n_mels).unwrap();
+
+        let outputs_gt = Python::attach(|py| {
+            _whisper_log_mel(py, x, n_mels).map_err(|e| {
+                eprintln!("Error calling _whisper_log_mel(): {:?}", e);
+                e.print_and_set_sys_last_vars(py);
+            })
+        })
+        .unwrap();
+
+        assert_close(
+            filters.as_slice_memory_order().unwrap(),
+            &outputs_gt[0],
+            1e-7,
+        );
+        assert_close(
+            log_spec.as_slice_memory_order().unwrap(),
+            &outputs_gt[1],
+            1e-4,
+        );
+    }
+}
+
+fn _scipy_welch<T: Float + Element>(py: Python, x: Array1<T>, kwargs: &str) -> PyResult<Vec<T>> {
+    let globals = PyDict::new(py);
+    globals.set_item("signal", py.import("scipy.signal")?)?;
+    globals.set_item("np", py.import("numpy")?)?;
+
+    let locals = PyDict::new(py);
+    locals.set_item("x", _to_numpy(py, &x)?)?;
+    let psd = _eval(
+        py,
+        &format!("signal.welch(x, {})[1].astype(x.dtype)", kwargs),
+        &globals,
+        Some(&locals),
+    )?;
+
+    _to_vec(&psd)
+}
+
+fn _test_welch(builder: WelchBuilder<f64>, kwargs: &str) {
+    let x = Array1::random(5000, Normal::new(0.5, 1.)) + Array1::linspace(0., 3., 5000);
+    let psd = builder.build().unwrap().process(&x).unwrap();
+
+    let psd_gt = Python::attach(|py| {
+        _scipy_welch(py, x, kwargs).map_err(|e| {
+            eprintln!("Error calling _scipy_welch(): {:?}", e);
+            e.print_and_set_sys_last_vars(py);
+        })
+    })
+    .unwrap();
+    assert_close(psd.as_slice().unwrap(), &psd_gt, 1e-10);
+}
+
+#[test]
+fn test_welch_scipy() {
+    _test_welch(WelchBuilder::new().fs(16000.), "fs=16000.");
+    _test_welch(
+        WelchBuilder::new()
+            .nperseg(300)
+            .noverlap(100)
+            .nfft(512)
+            .detrend(Detrend::Linear)
+            .window_named(Window::Hamming),
+        "nperseg=300, noverlap=100, nfft=512, detrend='linear', window='hamming'",
+    );
+    _test_welch(
+        WelchBuilder::new()
+            .nperseg(127)
+            .scaling(Scaling::Spectrum)
+            .average(Average::Median)
+            .detrend(Detrend::None)
+            .onesided(false),
+        "nperseg=127, scaling='spectrum', average='median', detrend=False, \
+         return_onesided=False",
+    );
+}
+
+fn _scipy_multitaper(
+    py: Python,
+    x: Array1<f64>,
+    n_fft: usize,
+    hop_length: usize,
+    nw: f64,
+    k: usize,
+) -> PyResult<Vec<f64>> {
+    let globals = PyDict::new(py);
+    globals.set_item("signal", py.import("scipy.signal")?)?;
+    globals.set_item("np", py.import("numpy")?)?;
+
+    let locals = PyDict::new(py);
+    locals.set_item("x", _to_numpy(py, &x)?)?;
+    locals.set_item(
+        "tapers",
+        _eval(
+            py,
+            &format!("signal.windows.dpss({}, {}, {}, sym=False)", n_fft, nw, k),
+            &globals,
+            None,
+        )?,
+    )?;
+    let frames = format!(
+        "np.lib.stride_tricks.sliding_window_view(x, {})[::{}]",
+        n_fft, hop_length
+    );
+    let power = format!(
+        "np.abs(np.fft.rfft(tapers[:, None] * {}[None], axis=-1)) ** 2",
+        frames
+    );
+    let psd = _eval(
+        py,
+        &format!("np.asfortranarray(({}).mean(axis=0).T)", power),
+        &globals,
+        Some(&locals),
+    )?;
+
+    _to_vec(&psd)
+}
+
+#[test]
+fn test_multitaper_scipy() {
+    let x = Array1::random(5000, Normal::new(0., 1.));
+    for &(n_fft, hop_length, nw, k) in &[(512, 128, 4., 7), (255, 100, 2.5, 3)] {
+        let psd = MultitaperBuilder::new()
+            .n_fft(n_fft)
+            .hop_length(hop_length)
+            .nw(nw)
+            .k(k)
+            .build()
+            .unwrap()
+            .process(&x)
+            .unwrap();
+
+        let psd_gt = Python::attach(|py| {
+            _scipy_multitaper(py, x.clone(), n_fft, hop_length, nw, k).map_err(|e| {
+                eprintln!("Error calling _scipy_multitaper(): {:?}", e);
+                e.print_and_set_sys_last_vars(py);
+            })
+        })
+        .unwrap();
+        assert_close(psd.as_slice_memory_order().unwrap(), &psd_gt, 1e-10);
+    }
+}
+
+/// Explicitly pads `x` like numpy.pad() does.
+fn _pad(x: &[f64], n_fft: usize, hop_length: usize, pad: PadMode<f64>) -> Vec<f64> {
+    let size = x.len();
+    let n_half = n_fft / 2;
+    let (front, end) = match pad {
+        PadMode::Truncate => (vec![], vec![]),
+        PadMode::End => (vec![], vec![0.; hop_length - (size - n_fft) % hop_length]),
+        PadMode::Center => {
+            let n_pad = hop_length - (size - n_fft) % hop_length;
+            (vec![0.; n_pad / 2], vec![0.; n_pad - n_pad / 2])
+        }
+        PadMode::Reflect => (
+            x[1..n_half + 1].iter().rev().cloned().collect(),
+            x[size - 1 - n_half..size - 1]
+                .iter()
+                .rev()
+                .cloned()
+                .collect(),
+        ),
+        PadMode::Edge => (vec![x[0]; n_half], vec![x[size - 1]; n_half]),
+        PadMode::Constant(v) => (vec![v; n_half], vec![v; n_half]),
+    };
+    let mut x_padded = front;
+    x_padded.extend(x);
+    x_padded.extend(end);
+    x_padded
+}
+
+fn _test_stft_view(size: usize, n_fft: usize, hop_length: usize, pad: PadMode<f64>) -> Result<()> {
+    // Non-contiguous view of the signal
+    let x = Array2::random((size, 2), Normal::new(0., 1.));
+    let x = x.column(1);
+    let stft = StftBuilder::<f64>::new()
+        .n_fft(n_fft)
+        .hop_length(hop_length)
+        .pad_mode(pad)
+        .build()?;
+    let spec = stft.process_view(x)?;
+    assert_eq!(spec, stft.process(x.to_vec())?);
+    assert_eq!(spec, stft.process_view(&x.to_vec()[..])?);
+    assert_eq!(
+        stft.process_complex_view(x)?,
+        stft.process_complex(x.to_vec())?
+    );
+
+    // The padding is equal to explicitly padding the signal
+    let x_padded = _pad(&x.to_vec(), n_fft, hop_length, pad);
+    let spec_gt = StftBuilder::<f64>::new()
+        .n_fft(n_fft)
+        .hop_length(hop_length)
+        .pad_mode(PadMode::Truncate)
+        .build()?
+        .process(x_padded)?;
+    assert_eq!(spec, spec_gt);
+    Ok(())
+}
+
+#[test]
+fn test_stft_view_truncate() {
+    _test_stft_view(4000, 1024, 441, PadMode::Truncate).unwrap();
+}
+#[test]
+fn test_stft_view_end() {
+    _test_stft_view(4000, 1024, 441, PadMode::End).unwrap();
+}
+#[test]
+fn test_stft_view_center() {
+    _test_stft_view(4000, 1024, 441, PadMode::Center).unwrap();
+    _test_stft_view(1000, 64, 100, PadMode::Center).unwrap();
+}
+#[test]
+fn test_stft_view_reflect() {
+    _test_stft_view(4000, 1024, 441, PadMode::Reflect).unwrap();
+    _test_stft_view(600, 1024, 100, PadMode::Reflect).unwrap();
+    _test_stft_view(1000, 65, 30, PadMode::Reflect).unwrap();
+}
+#[test]
+fn test_stft_view_edge() {
+    _test_stft_view(4000, 1024, 441, PadMode::Edge).unwrap();
+    _test_stft_view(100, 1024, 100, PadMode::Edge).unwrap();
+}
+#[test]
+fn test_stft_view_constant() {
+    _test_stft_view(4000, 1024, 441, PadMode::Constant(0.)).unwrap();
+    _test_stft_view(100, 1024, 100, PadMode::Constant(-0.5)).unwrap();
+}
+#[test]
+fn test_stft_process_into() {
+    let stft = StftBuilder::<f32>::new()
+        .n_fft(512)
+        .hop_length(128)
+        .pad_mode(PadMode::Center)
+        .build()
+        .unwrap();
+    let mut workspace = stft.workspace();
+    for &size in &[1000, 4000, 512] {
+        let x = Array1::random(size, F32(Normal::new(0., 1.)));
+        let mut spec = Array2::zeros(stft.output_shape(size).unwrap());
+        stft.process_into(&x, spec.view_mut(), &mut workspace)
+            .unwrap();
+        assert_eq!(spec, stft.process(x.to_vec()).unwrap());
+        let mut spec = Array2::zeros((257, 1));
+        assert!(stft
+            .process_into(&x, spec.view_mut(), &mut workspace)
+            .is_err());
+    }
+}
+#[test]
+fn test_stft_multichannel() {
+    let stft = StftBuilder::<f64>::new()
+        .n_fft(256)
+        .hop_length(100)
+        .pad_mode(PadMode::End)
+        .build()
+        .unwrap();
+    let x = Array3::random((2, 3, 2000), Normal::new(0., 1.));
+    let spec = stft.process_batch(&x).unwrap();
+    assert_eq!(spec.shape(), &[2, 3, 129, 19]);
+    for b in 0..2 {
+        let spec_channels = stft.process_multichannel(x.index_axis(Axis(0), b)).unwrap();
+        assert_eq!(spec_channels, spec.index_axis(Axis(0), b));
+        for c in 0..3 {
+            assert_eq!(
+                stft.process(x.slice(s![b, c, ..]).to_vec()).unwrap(),
+                spec.slice(s![b, c, .., ..])
+            );
+        }
+    }
+    // Also works for channels that are not contiguous in memory
+    let x = x.index_axis(Axis(0), 0).reversed_axes().to_owned();
+    let spec_channels = stft.process_multichannel(x.t()).unwrap();
+    assert_eq!(spec_channels, spec.index_axis(Axis(0), 0));
+}
+#[test]
+fn test_stft_output_mode() {
+    let x = Array1::random(4000, Normal::new(0., 1.));
+    let builder = || {
+        StftBuilder::<f64>::new()
+            .n_fft(512)
+            .hop_length(128)
+            .pad_mode(PadMode::Reflect)
+    };
+    let spec = builder().build().unwrap().process_complex_view(&x).unwrap();
+    let modes = [
+        OutputMode::Magnitude,
+        OutputMode::Power(2.),
+        OutputMode::Power(0.3),
+        OutputMode::LogMagnitude(1e-3),
+    ];
+    for mode in modes.iter().cloned() {
+        let f = |v: f64| match mode {
+            OutputMode::Magnitude => v,
+            OutputMode::Power(p) => v.powf(p),
+            OutputMode::LogMagnitude(eps) => v.max(eps).ln(),
+        };
+        let out = builder().output_mode(mode).build().unwrap();
+        let gt = spec.map(|v| f(v.norm()));
+        assert!(out.process_view(&x).unwrap().all_close(&gt, 1e-10));
+        let batch = out
+            .process_multichannel(x.view().insert_axis(Axis(0)))
+            .unwrap();
+        assert!(batch.index_axis(Axis(0), 0).all_close(&gt, 1e-10));
+    }
+}
+#[test]
+fn test_stft_phase() {
+    let x = Array1::random(4000, Normal::new(0., 1.));
+    let stft = StftBuilder::<f64>::new()
+        .n_fft(256)
+        .hop_length(64)
+        .pad_mode(PadMode::Reflect)
+        .build()
+        .unwrap();
+    let spec = stft.process_complex_view(&x).unwrap();
+    let phase = stft.process_phase(&x).unwrap();
+    assert!(phase.all_close(&spec.map(|v| v.arg()), 1e-12));
+
+    // The unwrapped phase equals the phase modulo 2 pi and changes less than pi per frame
+    let unwrapped = stft.process_unwrapped_phase(&x).unwrap();
+    assert_eq!(unwrapped.shape(), phase.shape());
+    let two_pi = 2. * std::f64::consts::PI;
+    Zip::from(&unwrapped).and(&phase).apply(|&u, &p| {
+        let d = (u - p) / two_pi;
+        assert!((d - d.round()).abs() < 1e-9);
+    });
+    let diff = &unwrapped.slice(s![.., 1..]) - &unwrapped.slice(s![.., ..-1]);
+    assert!(diff.iter().all(|d| d.abs() <= std::f64::consts::PI + 1e-9));
+}
+#[test]
+fn test_stft_instantaneous_frequency() {
+    let (sr, f0) = (16000., 1234.5);
+    let n_fft = 512;
+    let x = Array1::from_shape_fn(8000, |i| {
+        (2. * std::f64::consts::PI * f0 * i as f64 / sr).cos()
+    });
+    let stft = StftBuilder::<f64>::new()
+        .n_fft(n_fft)
+        .hop_length(128)
+        .build()
+        .unwrap();
+    let freqs = stft.process_instantaneous_frequency(&x, sr).unwrap();
+    assert_eq!(freqs.dim(), stft.output_shape(x.len()).unwrap());
+    // Bin center frequencies in the first frame
+    assert!((freqs[[10, 0]] - 10. * sr / n_fft as f64).abs() < 1e-9);
+    // The bins next to the sinusoid follow its frequency
+    let bin = (f0 / sr * n_fft as f64).round() as usize;
+    for &f in freqs.slice(s![bin - 1..bin + 2, 1..]).iter() {
+        assert!((f - f0).abs() < 1., "{} !~ {}", f, f0);
+    }
+}
+#[test]
+fn test_stft_group_delay() {
+    let n_fft = 256;
+    let mut x = Array1::zeros(2000);
+    x[1000] = 1.;
+    let stft = StftBuilder::<f64>::new()
+        .n_fft(n_fft)
+        .hop_length(100)
+        .window(Array1::ones(n_fft))
+        .build()
+        .unwrap();
+    let delay = stft.process_group_delay(&x).unwrap();
+    assert_eq!(delay.dim(), stft.output_shape(x.len()).unwrap());
+    // Frame 8 starts at sample 800 and contains the impulse at position 200
+    for &d in delay.column(8).iter() {
+        assert!((d - 200.).abs() < 1e-6, "{} !~ 200", d);
+    }
+}
+#[test]
+fn test_stft_too_short() {
+    let stft = StftBuilder::<f32>::new().n_fft(16).build().unwrap();
+    assert!(stft.process(vec![0.; 15]).is_err());
+    let stft = StftBuilder::<f32>::new()
+        .n_fft(16)
+        .pad_mode(PadMode::Reflect)
+        .build()
+        .unwrap();
+    assert!(stft.process(vec![]).is_err());
+    assert_eq!(stft.process(vec![0.; 8]).unwrap().shape(), &[9, 3]);
+}
+
+#[test]
+fn test_stft_reflect_short() {
+    // The padding is longer than the signal and reflected repeatedly
+    let x = [1., 2., 4.];
+    let padded = [
+        1., 2., 4., 2., 1., 2., 4., 2., 1., 2., 4., 2., 1., 2., 4., 2., 1., 2., 4.,
+    ];
+    let stft = StftBuilder::<f64>::new()
+        .n_fft(16)
+        .hop_length(1)
+        .window_named(Window::Boxcar)
+        .normalize(false)
+        .pad_mode(PadMode::Reflect)
+        .build()
+        .unwrap();
+    let spec = stft.process(x.to_vec()).unwrap();
+    assert_eq!(spec.shape(), &[9, 4]);
+    for (t, &dc) in spec.row(0).iter().enumerate() {
+        let sum: f64 = padded[t..t + 16].iter().sum();
+        assert!((dc - sum).abs() < 1e-12, "{} != {}", dc, sum);
+    }
+}
@@ -0,0 +1,81 @@
+extern crate audio_featrs;
+extern crate ndarray;
+extern crate ndarray_rand;
+extern crate rand;
+
+use audio_featrs::{PadMode, StftBuilder};
+use ndarray::{prelude::*, stack};
+use ndarray_rand::RandomExt;
+use rand::distributions::Normal;
+use rand::Rng;
+
+fn _test_streaming(
+    size: usize,
+    n_fft: usize,
+    hop_length: usize,
+    pad: PadMode<f64>,
+    max_chunk: usize,
+) {
+    let x = Array1::random(size, Normal::new(0., 1.)).to_vec();
+    let builder = || {
+        StftBuilder::<f64>::new()
+            .n_fft(n_fft)
+            .hop_length(hop_length)
+    };
+    let spec = builder()
+        .pad_mode(match pad {
+            PadMode::End => PadMode::End,
+            _ => PadMode::Truncate,
+        })
+        .build()
+        .unwrap()
+        .process(x.clone())
+        .unwrap();
+
+    let mut streaming = builder().pad_mode(pad).build_streaming().unwrap();
+    let mut rng = rand::thread_rng();
+    let mut frames = Vec::new();
+    let mut pos = 0;
+    while pos < size {
+        let n = rng.gen_range(0, max_chunk + 1).min(size - pos);
+        frames.push(streaming.push(&x[pos..pos + n]).unwrap());
+        pos += n;
+    }
+    frames.push(streaming.flush().unwrap());
+    let views = frames.iter().map(|f| f.view()).collect::<Vec<_>>();
+    let spec_streaming = stack(Axis(1), &views).unwrap();
+    assert_eq!(spec_streaming, spec);
+}
+
+#[test]
+fn test_streaming_truncate_small_chunks() {
+    _test_streaming(4000, 512, 128, PadMode::Truncate, 10);
+}
+#[test]
+fn test_streaming_truncate_large_chunks() {
+    _test_streaming(4000, 512, 441, PadMode::Truncate, 2000);
+}
+#[test]
+fn test_streaming_end() {
+    _test_streaming(4000, 512, 128, PadMode::End, 300);
+}
+#[test]
+fn test_streaming_hop_larger_n_fft() {
+    _test_streaming(4000, 64, 100, PadMode::Truncate, 50);
+    _test_streaming(4000, 64, 100, PadMode::End, 150);
+}
+
+#[test]
+fn test_streaming_center_not_supported() {
+    for &pad in &[
+        PadMode::Center,
+        PadMode::Reflect,
+        PadMode::Edge,
+        PadMode::Constant(0.),
+    ] {
+        assert!(StftBuilder::<f32>::new()
+            .pad_mode(pad)
+            .build_streaming()
+            .is_err());
+    }
+}
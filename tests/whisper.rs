@@ -0,0 +1,62 @@
+extern crate audio_featrs;
+#[macro_use]
+extern crate ndarray;
+extern crate ndarray_rand;
+extern crate rand;
+
+use std::f64::consts::PI;
+
+use audio_featrs::whisper;
+use ndarray::prelude::*;
+use ndarray_rand::RandomExt;
+use rand::distributions::Normal;
+
+#[test]
+fn test_pad_or_trim() {
+    let x = Array1::random(1000, Normal::new(0., 1.));
+    let padded = whisper::pad_or_trim(&x, 1500);
+    assert_eq!(padded.slice(s![..1000]), x);
+    assert!(padded.slice(s![1000..]).iter().all(|&v| v == 0.));
+    assert_eq!(whisper::pad_or_trim(&x, 600), x.slice(s![..600]));
+}
+
+#[test]
+fn test_whisper_mel_filters() {
+    for &n_mels in &[80, 128] {
+        let filters = whisper::mel_filters::<f64>(n_mels).unwrap();
+        assert_eq!(filters.dim(), (n_mels, 201));
+        assert!(filters.iter().all(|&v| v >= 0.));
+        // Unit area in Hz, up to the sampling of the narrow filters by the FFT bins
+        let df = 8000. / 200.;
+        for filter in filters.slice(s![n_mels / 2.., ..]).outer_iter() {
+            assert!((filter.scalar_sum() * df - 1.).abs() < 0.1);
+        }
+    }
+    assert!(whisper::mel_filters::<f64>(64).is_err());
+}
+
+#[test]
+fn test_whisper_log_mel_spectrogram() {
+    // A 1 kHz tone of 5 s, the rest is zero padding
+    let sr = whisper::SAMPLE_RATE as f64;
+    let x = Array1::from_shape_fn(5 * whisper::SAMPLE_RATE, |i| {
+        (2. * PI * 1000. * i as f64 / sr).sin() as f32
+    });
+    let log_spec = whisper::log_mel_spectrogram(&x, 80).unwrap();
+    assert_eq!(log_spec.dim(), (80, whisper::N_FRAMES));
+
+    let max = log_spec.fold(f32::NEG_INFINITY, |a, &b| a.max(b));
+    let min = log_spec.fold(f32::INFINITY, |a, &b| a.min(b));
+    assert!((max - min - 2.).abs() < 1e-6);
+    // The padding is clamped to the floor
+    assert!(log_spec.slice(s![.., 600..]).iter().all(|&v| v == min));
+
+    // 1 kHz is the 25th bin of the STFT
+    let filters = whisper::mel_filters::<f32>(80).unwrap();
+    let argmax = |v: ArrayView1<f32>| {
+        v.iter()
+            .enumerate()
+            .fold(0, |m, (i, &x)| if x > v[m] { i } else { m })
+    };
+    assert_eq!(argmax(log_spec.column(250)), argmax(filters.column(25)));
+}
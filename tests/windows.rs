@@ -1,4 +1,5 @@
 extern crate audio_featrs;
+extern crate ndarray;
 extern crate num_traits;
 
 use audio_featrs::windows::*;
@@ -96,3 +97,114 @@ fn test_hann() {
         1e-10,
     );
 }
+
+#[test]
+fn test_time_weighted() {
+    let w = ndarray::Array1::<f64>::ones(4);
+    assert_close(
+        time_weighted(&w).as_slice().unwrap(),
+        &[-1.5, -0.5, 0.5, 1.5],
+        1e-10,
+    );
+    let w = hann::<f64>(5, true);
+    assert_close(
+        time_weighted(&w).as_slice().unwrap(),
+        &[0., -0.5, 0., 0.5, 0.],
+        1e-10,
+    );
+}
+
+#[test]
+fn test_derivative() {
+    // np.gradient(np.pad(w, 1, mode="wrap"))[1:-1]
+    let w = ndarray::Array1::from_vec(vec![1., 2., 4., 7.]);
+    assert_close(
+        derivative(&w).as_slice().unwrap(),
+        &[-2.5, 1.5, 2.5, -1.5],
+        1e-10,
+    );
+}
+
+#[test]
+fn test_dpss() {
+    // Reference values of scipy.signal.windows.dpss(8, 2, 3, return_ratios=True)
+    let (tapers, ratios) = dpss::<f64>(8, 2., 3, true);
+    assert_eq!(tapers.shape(), &[3, 8]);
+    let expected: [&[f64]; 3] = [
+        &[
+            0.04986698658256845,
+            0.1904720764793774,
+            0.3953020578405855,
+            0.5522408485414734,
+            0.5522408485414734,
+            0.3953020578405855,
+            0.1904720764793774,
+            0.04986698658256845,
+        ],
+        &[
+            0.17263018791800613,
+            0.41946337594550404,
+            0.4938487324606203,
+            0.22441640739222543,
+            -0.22441640739222543,
+            -0.4938487324606203,
+            -0.41946337594550404,
+            -0.17263018791800613,
+        ],
+        &[
+            0.3820382157158875,
+            0.48973614316838365,
+            0.14335783144427827,
+            -0.30602915542603354,
+            -0.30602915542603354,
+            0.14335783144427827,
+            0.48973614316838365,
+            0.3820382157158875,
+        ],
+    ];
+    for (taper, expected) in tapers.outer_iter().zip(expected.iter()) {
+        assert_close(&taper.to_vec(), expected, 1e-12);
+    }
+    assert_close(
+        ratios.as_slice().unwrap(),
+        &[0.9999838545284161, 0.9988618643803536, 0.9714518874493441],
+        1e-12,
+    );
+
+    // dpss(7, 1.5, 2, sym=False)
+    let (tapers, ratios) = dpss::<f64>(7, 1.5, 2, false);
+    assert_close(
+        &tapers.row(1).to_vec(),
+        &[
+            0.2831143776189667,
+            0.4521303816691347,
+            0.4290668257778468,
+            0.17699159916241947,
+            -0.17699159916241947,
+            -0.4290668257778468,
+            -0.4521303816691347,
+        ],
+        1e-12,
+    );
+    assert_close(
+        ratios.as_slice().unwrap(),
+        &[0.9993005492156002, 0.9745656852157323],
+        1e-12,
+    );
+}
+
+#[test]
+fn test_dpss_orthonormal() {
+    let (tapers, ratios) = dpss::<f64>(1024, 4., 7, true);
+    let gram = tapers.dot(&tapers.t());
+    for i in 0..7 {
+        for j in 0..7 {
+            let expected = if i == j { 1. } else { 0. };
+            assert!((gram[[i, j]] - expected).abs() < 1e-9, "{:?}", gram);
+        }
+    }
+    for w in ratios.windows(2) {
+        assert!(w[0] > w[1] && w[0] <= 1.);
+    }
+    assert!(ratios[6] > 0.9);
+}